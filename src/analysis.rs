@@ -0,0 +1,15 @@
+mod ac;
+mod dc_sweep;
+mod mesh;
+mod power;
+mod superposition;
+mod thevenin;
+mod transient;
+
+pub use self::ac::{AcResult, AcSweep};
+pub use self::dc_sweep::{DcSweep, DcSweepResult};
+pub use self::mesh::{MeshLoop, MeshResult};
+pub use self::power::PowerReport;
+pub use self::superposition::SuperpositionResult;
+pub use self::thevenin::TheveninEquivalent;
+pub use self::transient::TransientResult;
@@ -0,0 +1,187 @@
+use nalgebra::{Complex, ComplexField};
+use serde::{Deserialize, Serialize};
+
+use crate::error::CircuitError;
+use crate::solver::Solver;
+use crate::sparse::{SparseLu, SparseMatrix};
+
+/// How the frequencies of an AC sweep are spaced.
+/// Linear holds the total number of points, Decade and Octave the number of points per decade/octave.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AcSweep {
+    Linear(usize),
+    Decade(usize),
+    Octave(usize),
+}
+
+impl AcSweep {
+    /// The frequencies of the sweep from f_start to f_stop (inclusive) in Hz.
+    /// A logarithmic sweep starting at or below 0Hz has no frequencies.
+    pub fn frequencies(&self, f_start: f64, f_stop: f64) -> Vec<f64> {
+        // a little slack so rounding does not drop the last point
+        let limit = f_stop * (1.0 + 1e-9);
+        match *self {
+            AcSweep::Linear(points) => match points {
+                0 => Vec::new(),
+                1 => vec![f_start],
+                _ => {
+                    let step = (f_stop - f_start) / (points - 1) as f64;
+                    (0..points).map(|k| f_start + k as f64 * step).collect()
+                }
+            },
+            AcSweep::Decade(points) => Self::logarithmic(f_start, limit, 10.0, points),
+            AcSweep::Octave(points) => Self::logarithmic(f_start, limit, 2.0, points),
+        }
+    }
+
+    fn logarithmic(f_start: f64, limit: f64, base: f64, points: usize) -> Vec<f64> {
+        if points == 0 || f_start <= 0.0 {
+            return Vec::new();
+        }
+        let ratio = base.powf(1.0 / points as f64);
+        (0..)
+            .map(|k| f_start * ratio.powi(k))
+            .take_while(|f| *f <= limit)
+            .collect()
+    }
+}
+
+/*
+* The sparse complex matrix M and the vector b of the system M * x = b.
+*/
+type AcSystem = (SparseMatrix<Complex<f64>>, Vec<Complex<f64>>);
+
+/// The result of an AC sweep.
+/// `potentials[k]` and `currents[k]` hold the complex node potentials and component currents at `frequencies[k]`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AcResult {
+    pub frequencies: Vec<f64>,
+    pub potentials: Vec<Vec<Complex<f64>>>,
+    pub currents: Vec<Vec<Complex<f64>>>,
+}
+
+impl AcResult {
+    pub fn len(&self) -> usize {
+        self.frequencies.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frequencies.is_empty()
+    }
+
+    /// The magnitude of the potential of a node at `frequencies[k]`.
+    pub fn magnitude(&self, k: usize, node_id: usize) -> f64 {
+        self.potentials[k][node_id].modulus()
+    }
+
+    /// The phase in degrees of the potential of a node at `frequencies[k]`.
+    pub fn phase(&self, k: usize, node_id: usize) -> f64 {
+        self.potentials[k][node_id].argument().to_degrees()
+    }
+
+    /// The magnitude of a node potential at every frequency.
+    pub fn node_magnitude(&self, node_id: usize) -> Vec<f64> {
+        self.potentials
+            .iter()
+            .map(|p| p[node_id].modulus())
+            .collect()
+    }
+
+    /// The phase of a node potential in degrees at every frequency.
+    pub fn node_phase(&self, node_id: usize) -> Vec<f64> {
+        (0..self.len()).map(|k| self.phase(k, node_id)).collect()
+    }
+}
+
+/*
+* this impl block contains the small signal AC analysis of a circuit.
+*/
+impl Solver {
+    /// Solves the circuit in the frequency domain at every frequency of the sweep.
+    /// Resistors, capacitors and inductors are stamped as impedances and independent sources
+    /// only contribute their AC magnitude and phase.
+    /// Nonlinear components are linearized around the DC operating point, which is solved first.
+    pub fn ac(
+        &mut self,
+        sweep: AcSweep,
+        f_start: f64,
+        f_stop: f64,
+    ) -> Result<AcResult, CircuitError> {
+        if !(f_start >= 0.0 && f_stop >= f_start) {
+            return Err(CircuitError::InvalidParameter(format!(
+                "Invalid AC sweep from {f_start}Hz to {f_stop}Hz"
+            )));
+        }
+        if f_start <= 0.0 && !matches!(sweep, AcSweep::Linear(_)) {
+            return Err(CircuitError::InvalidParameter(format!(
+                "A logarithmic AC sweep has to start above 0Hz, got {f_start}Hz"
+            )));
+        }
+        if self.components().iter().any(|c| c.is_nonlinear()) {
+            self.solve_system()?;
+        }
+        let num_nodes = self.nodes().len();
+        let frequencies = sweep.frequencies(f_start, f_stop);
+        let mut result = AcResult {
+            frequencies: frequencies.clone(),
+            potentials: Vec::with_capacity(frequencies.len()),
+            currents: Vec::with_capacity(frequencies.len()),
+        };
+
+        for frequency in frequencies {
+            let omega = 2.0 * std::f64::consts::PI * frequency;
+            let (m, b) = self.build_ac_system(omega)?;
+            let mut x = SparseLu::new(&m)?.solve(&b);
+            result.currents.push(x.split_off(num_nodes));
+            result.potentials.push(x);
+        }
+        Ok(result)
+    }
+
+    /*
+     * this method fills the complex matrix M and the vector b of the system M * x = b at omega
+     */
+    pub(crate) fn build_ac_system(&self, omega: f64) -> Result<AcSystem, CircuitError> {
+        if !self.circuit.is_locked() {
+            return Err(CircuitError::NotLocked);
+        }
+        let num_unknowns = self.circuit.num_variables();
+        let num_nodes = self.nodes().len();
+
+        let mut m = SparseMatrix::new(num_unknowns);
+        let mut b = vec![Complex::from(0.0); num_unknowns];
+
+        // KCL, the currents at a node are the same in both domains
+        let mut kcl = vec![0.0; num_unknowns];
+        for node_id in 0..num_nodes {
+            self.circuit.currents_at_node_eq(node_id, &mut kcl)?;
+            for col in self.circuit.node_columns(node_id)? {
+                let value = std::mem::replace(&mut kcl[col], 0.0);
+                m.push(node_id, col, Complex::from(value));
+            }
+            if let Some(column) = kcl.iter().position(|v| *v != 0.0) {
+                return Err(CircuitError::InvalidStamp {
+                    row: node_id,
+                    column,
+                });
+            }
+        }
+
+        // Do the component-related equations
+        let mut row = vec![Complex::from(0.0); num_unknowns];
+        let mut row_id = num_nodes;
+        for c in self.components() {
+            let columns = c.stamp_columns(row_id);
+            for eq_id in 0..c.get_currents() {
+                if row_id >= num_unknowns {
+                    return Err(CircuitError::NotLocked);
+                }
+                b[row_id] = c.ac_equation(row_id - eq_id, &mut row, eq_id, omega)?;
+                m.take_columns(row_id, &mut row, &columns)?;
+                row_id += 1;
+            }
+        }
+
+        Ok((m, b))
+    }
+}
@@ -0,0 +1,107 @@
+use serde::{Deserialize, Serialize};
+
+use crate::error::CircuitError;
+use crate::solver::Solver;
+
+/// The values a DC sweep steps through.
+/// Linear goes from start to stop (inclusive) in steps of step, List uses the given values in order.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum DcSweep {
+    Linear { start: f64, stop: f64, step: f64 },
+    List(Vec<f64>),
+}
+
+impl DcSweep {
+    /// The values of the sweep, a linear sweep counts down if stop is below start.
+    pub fn values(&self) -> Result<Vec<f64>, CircuitError> {
+        match self {
+            DcSweep::Linear { start, stop, step } => {
+                if !(*step > 0.0 && start.is_finite() && stop.is_finite()) {
+                    return Err(CircuitError::InvalidParameter(format!(
+                        "Invalid DC sweep from {start} to {stop} in steps of {step}"
+                    )));
+                }
+                // a little slack so rounding does not drop the last point
+                let points = ((stop - start).abs() / step + 1e-9).floor() as usize;
+                let step = if stop < start { -step } else { *step };
+                Ok((0..=points).map(|k| start + k as f64 * step).collect())
+            }
+            DcSweep::List(values) => Ok(values.clone()),
+        }
+    }
+}
+
+/// The result of a DC sweep.
+/// `potentials[k]` and `currents[k]` hold the node potentials and component currents at `values[k]`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DcSweepResult {
+    pub component: String,
+    pub values: Vec<f64>,
+    pub potentials: Vec<Vec<f64>>,
+    pub currents: Vec<Vec<f64>>,
+}
+
+impl DcSweepResult {
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// The potential of a node at every value of the sweep.
+    pub fn node_values(&self, node_id: usize) -> Vec<f64> {
+        self.potentials.iter().map(|p| p[node_id]).collect()
+    }
+
+    /// A component current at every value of the sweep, index is relative to the first current.
+    pub fn current_values(&self, index: usize) -> Vec<f64> {
+        self.currents.iter().map(|c| c[index]).collect()
+    }
+}
+
+/*
+* this impl block contains the DC sweep analysis of a circuit.
+*/
+impl Solver {
+    /// Solves the operating point of the circuit for every value of the sweep, the value sets
+    /// the voltage of a voltage source, the current of a current source or the resistance of a
+    /// resistor with the given name. Each point starts from the solution of the previous one.
+    pub fn dc_sweep(
+        &mut self,
+        component: &str,
+        sweep: &DcSweep,
+    ) -> Result<DcSweepResult, CircuitError> {
+        let comp_id = self.circuit.find_component(component)?;
+        let original = self.circuit.components[comp_id].sweep_value()?;
+        let values = sweep.values()?;
+
+        let result = self.run_dc_sweep(comp_id, values);
+        // the component gets its own value back even if the sweep failed
+        self.circuit.components[comp_id].set_sweep_value(original)?;
+        result
+    }
+
+    fn run_dc_sweep(
+        &mut self,
+        comp_id: usize,
+        values: Vec<f64>,
+    ) -> Result<DcSweepResult, CircuitError> {
+        let num_nodes = self.nodes().len();
+        let mut result = DcSweepResult {
+            component: self.circuit.components[comp_id].get_name(),
+            values: Vec::with_capacity(values.len()),
+            potentials: Vec::with_capacity(values.len()),
+            currents: Vec::with_capacity(values.len()),
+        };
+        for value in values {
+            self.circuit.components[comp_id].set_sweep_value(value)?;
+            let mut x = self.solve_system()?;
+            result.values.push(value);
+            result.currents.push(x.split_off(num_nodes));
+            result.potentials.push(x);
+        }
+        Ok(result)
+    }
+}
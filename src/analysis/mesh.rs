@@ -0,0 +1,491 @@
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BinaryHeap};
+
+use serde::{Deserialize, Serialize};
+
+use crate::components::{BranchKind, Component, ConnectionType};
+use crate::error::CircuitError;
+use crate::solution::Solution;
+use crate::solver::Solver;
+use crate::sparse::{SparseLu, SparseMatrix};
+
+// the largest difference from the nodal result, relative to the largest value, of an agreeing result
+const AGREEMENT_TOLERANCE: f64 = 1e-6;
+
+/// A loop of the fundamental loop basis, the link that closes it and the path back through the
+/// spanning tree. components holds every component the loop passes through with +1 when the loop
+/// goes through it in the direction of its branch and -1 when it goes against it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MeshLoop {
+    pub link: String,
+    pub components: Vec<(String, f64)>,
+}
+
+/// The result of a mesh analysis.
+/// `mesh_currents[l]` is the current flowing around `loops[l]`, potentials and currents are the node
+/// potentials and component currents they add up to, in the same order as a nodal solution.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MeshResult {
+    pub loops: Vec<MeshLoop>,
+    pub mesh_currents: Vec<f64>,
+    pub potentials: Vec<f64>,
+    pub currents: Vec<f64>,
+}
+
+impl MeshResult {
+    /// The largest difference between the potentials and currents of the mesh analysis and
+    /// those of a nodal solution of the same circuit.
+    pub fn deviation(&self, solution: &Solution) -> f64 {
+        self.values()
+            .zip(solution.potentials.iter().chain(&solution.currents))
+            .map(|(mesh, nodal)| (mesh - nodal).abs())
+            .fold(0.0, f64::max)
+    }
+
+    /// Checks that both methods found the same operating point up to rounding.
+    pub fn agrees_with(&self, solution: &Solution) -> bool {
+        let scale = self.values().map(|v| v.abs()).fold(0.0, f64::max);
+        self.potentials.len() == solution.potentials.len()
+            && self.currents.len() == solution.currents.len()
+            && self.deviation(solution) <= AGREEMENT_TOLERANCE * scale + f64::EPSILON
+    }
+
+    fn values(&self) -> impl Iterator<Item = &f64> {
+        self.potentials.iter().chain(&self.currents)
+    }
+}
+
+/*
+* An affine function of the unknowns of the mesh equations, the coefficients are stored by the
+* index of their unknown.
+*/
+#[derive(Debug, Clone, Default)]
+struct Linear {
+    coefficients: BTreeMap<usize, f64>,
+    constant: f64,
+}
+
+impl Linear {
+    fn unknown(index: usize) -> Self {
+        let mut res = Self::default();
+        res.add_term(index, 1.0);
+        res
+    }
+
+    fn add_term(&mut self, index: usize, coefficient: f64) {
+        *self.coefficients.entry(index).or_insert(0.0) += coefficient;
+    }
+
+    fn add_scaled(&mut self, other: &Linear, factor: f64) {
+        for (&index, coefficient) in other.coefficients.iter() {
+            self.add_term(index, factor * coefficient);
+        }
+        self.constant += factor * other.constant;
+    }
+
+    fn evaluate(&self, x: &[f64]) -> f64 {
+        self.coefficients
+            .iter()
+            .map(|(&index, c)| c * x[index])
+            .sum::<f64>()
+            + self.constant
+    }
+}
+
+/*
+* A branch of the circuit graph, from and to are node ids where the reference is one past the
+* last node. current is the index of the component current that flows through the branch from
+* from to to, times sign.
+*/
+struct MeshBranch {
+    comp_id: usize,
+    kind: BranchKind,
+    from: usize,
+    to: usize,
+    current: usize,
+    sign: f64,
+}
+
+/*
+* How the voltage of a branch enters the loop equations. An impedance branch has its voltage
+* given by its own current, V = (b - c * I) / a, every other branch gets its voltage as an
+* unknown and the equations of its component are added to the system.
+*/
+enum BranchVoltage {
+    Impedance { a: f64, b: f64, c: f64 },
+    Unknown(usize),
+}
+
+/*
+* this impl block contains the mesh analysis of a circuit.
+*/
+impl Solver {
+    /// Solves the circuit with loop currents instead of node potentials. The loops are the
+    /// fundamental loops of a spanning tree of the circuit graph, every branch outside the tree
+    /// closes one loop. Voltage sources are put in the tree first and current sources last, so
+    /// a current source usually carries a single loop current.
+    /// A branch whose voltage follows from its current (a resistor, a voltage source) is
+    /// substituted into the loop equations, any other branch gets its voltage as an extra
+    /// unknown together with the equations of its component. Only linear circuits are supported.
+    pub fn mesh(&self) -> Result<MeshResult, CircuitError> {
+        let circuit = &self.circuit;
+        if !circuit.is_locked() {
+            return Err(CircuitError::NotLocked);
+        }
+        if let Some(component) = self.components().iter().find(|c| c.is_nonlinear()) {
+            return Err(CircuitError::InvalidParameter(format!(
+                "mesh analysis needs a linear circuit but {} is nonlinear",
+                component.get_name()
+            )));
+        }
+        let num_nodes = circuit.nodes.len();
+        let num_variables = circuit.num_variables();
+        let reference = num_nodes;
+
+        let branches = self.mesh_branches()?;
+        let SpanningTree {
+            parents,
+            order,
+            links,
+        } = spanning_tree(&branches, reference, num_nodes + 1);
+        if let Some(node_id) = (0..num_nodes).find(|&n| parents[n].is_none()) {
+            return Err(CircuitError::InvalidParameter(format!(
+                "mesh analysis needs a path of branches from node {} to ground",
+                circuit.nodes[node_id].get_name()
+            )));
+        }
+        let loops: Vec<Vec<(usize, f64)>> = links
+            .iter()
+            .map(|&link| fundamental_loop(&branches, &parents, link))
+            .collect::<Result<_, _>>()?;
+
+        // the unknowns are the loop currents followed by the branch voltages and component
+        // currents that are not given by the loop currents
+        let mut num_unknowns = loops.len();
+        let mut voltages = Vec::with_capacity(branches.len());
+        let mut constrained = Vec::new();
+        for (comp_id, comp) in self.components().iter().enumerate() {
+            let own: Vec<&MeshBranch> = branches.iter().filter(|b| b.comp_id == comp_id).collect();
+            let offset = circuit.get_current_index(comp)?;
+            if let [branch] = own[..] {
+                if let Some(voltage) = impedance(comp, offset, branch, num_nodes, num_variables)? {
+                    voltages.push(voltage);
+                    continue;
+                }
+            }
+            for _ in own {
+                voltages.push(BranchVoltage::Unknown(num_unknowns));
+                num_unknowns += 1;
+            }
+            constrained.push(comp_id);
+        }
+        let untied: Vec<usize> = (num_nodes..num_variables)
+            .filter(|&i| !branches.iter().any(|b| b.current == i))
+            .collect();
+        let num_unknowns = num_unknowns + untied.len();
+
+        let mut x = vec![Linear::default(); num_variables];
+        for (k, &index) in untied.iter().enumerate() {
+            x[index] = Linear::unknown(num_unknowns - untied.len() + k);
+        }
+        // the current of every branch is the sum of the loop currents through it
+        for (l, coefficients) in loops.iter().enumerate() {
+            for &(b, coefficient) in coefficients {
+                x[branches[b].current].add_term(l, coefficient * branches[b].sign);
+            }
+        }
+        let branch_voltages: Vec<Linear> = branches
+            .iter()
+            .zip(&voltages)
+            .map(|(branch, voltage)| match voltage {
+                BranchVoltage::Impedance { a, b, c } => {
+                    let mut v = Linear {
+                        constant: b / a,
+                        ..Default::default()
+                    };
+                    v.add_scaled(&x[branch.current], -c / a);
+                    v
+                }
+                BranchVoltage::Unknown(index) => Linear::unknown(*index),
+            })
+            .collect();
+        // the potential of a node is the one of its parent minus the voltage of the tree branch
+        // between them, V(from) - V(to)
+        let mut potentials = vec![Linear::default(); num_nodes + 1];
+        for &v in order.iter() {
+            let Some((b, parent)) = parents[v] else {
+                continue;
+            };
+            let mut potential = potentials[parent].clone();
+            let sign = if branches[b].to == v { -1.0 } else { 1.0 };
+            potential.add_scaled(&branch_voltages[b], sign);
+            potentials[v] = potential;
+        }
+        for node_id in 0..num_nodes {
+            x[circuit.get_potential_index(node_id)] = potentials[node_id].clone();
+        }
+
+        // KVL around every loop, then the equations of the components with unknown voltages
+        let mut equations = Vec::with_capacity(num_unknowns);
+        for coefficients in loops.iter() {
+            let mut kvl = Linear::default();
+            for &(b, coefficient) in coefficients {
+                kvl.add_scaled(&branch_voltages[b], coefficient);
+            }
+            equations.push(kvl);
+        }
+        let mut row = vec![0.0; num_variables];
+        for &comp_id in constrained.iter() {
+            let comp = &self.components()[comp_id];
+            let offset = circuit.get_current_index(comp)?;
+            for eq_id in 0..comp.get_currents() {
+                row.iter_mut().for_each(|v| *v = 0.0);
+                let mut equation = Linear {
+                    constant: -comp.equation(offset, &mut row, eq_id)?,
+                    ..Default::default()
+                };
+                for (k, &value) in row.iter().enumerate().filter(|(_, v)| **v != 0.0) {
+                    equation.add_scaled(&x[k], value);
+                }
+                equations.push(equation);
+            }
+        }
+        if equations.len() != num_unknowns {
+            return Err(CircuitError::InvalidParameter(format!(
+                "mesh analysis found {} equations for {num_unknowns} unknowns",
+                equations.len()
+            )));
+        }
+
+        let mut m = SparseMatrix::new(num_unknowns);
+        let mut b = vec![0.0; num_unknowns];
+        for (r, equation) in equations.iter().enumerate() {
+            for (&col, &value) in equation.coefficients.iter() {
+                if value != 0.0 {
+                    m.push(r, col, value);
+                }
+            }
+            b[r] = -equation.constant;
+        }
+        let solution = SparseLu::new(&m)?.solve(&b);
+
+        let values: Vec<f64> = x.iter().map(|e| e.evaluate(&solution)).collect();
+        let (potentials, currents) = values.split_at(num_nodes);
+        let components = self.components();
+        Ok(MeshResult {
+            loops: links
+                .iter()
+                .zip(&loops)
+                .map(|(&link, coefficients)| MeshLoop {
+                    link: components[branches[link].comp_id].get_name(),
+                    components: coefficients
+                        .iter()
+                        .map(|&(b, c)| (components[branches[b].comp_id].get_name(), c))
+                        .collect(),
+                })
+                .collect(),
+            mesh_currents: solution[..loops.len()].to_vec(),
+            potentials: potentials.to_vec(),
+            currents: currents.to_vec(),
+        })
+    }
+
+    /*
+     * The branches of every component, with the component current that flows through each.
+     * The current of a branch is the one its to pin supplies to its node.
+     */
+    fn mesh_branches(&self) -> Result<Vec<MeshBranch>, CircuitError> {
+        let reference = self.nodes().len();
+        let mut branches = Vec::new();
+        for (comp_id, comp) in self.components().iter().enumerate() {
+            let offset = self.circuit.get_current_index(comp)?;
+            let node = |pin: ConnectionType| {
+                comp.get_connection(pin)?
+                    .get_id()
+                    .ok_or_else(|| CircuitError::DisconnectedPin {
+                        component: comp.get_name(),
+                        pin,
+                    })
+            };
+            for branch in comp.get_branches() {
+                let mut representative = vec![0.0; comp.get_currents()];
+                comp.current_representative(0, branch.to, &mut representative)?;
+                let mut nonzero = representative
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, v)| **v != 0.0);
+                let (Some((j, &sign)), None) = (nonzero.next(), nonzero.next()) else {
+                    return Err(unsupported(comp));
+                };
+                if sign.abs() != 1.0
+                    || branches
+                        .iter()
+                        .any(|b: &MeshBranch| b.current == offset + j)
+                {
+                    return Err(unsupported(comp));
+                }
+                branches.push(MeshBranch {
+                    comp_id,
+                    kind: branch.kind,
+                    from: branch.from.map(node).transpose()?.unwrap_or(reference),
+                    to: node(branch.to)?,
+                    current: offset + j,
+                    sign,
+                });
+            }
+        }
+        Ok(branches)
+    }
+}
+
+fn unsupported(component: &Component) -> CircuitError {
+    CircuitError::InvalidParameter(format!(
+        "mesh analysis does not support {}",
+        component.get_name()
+    ))
+}
+
+/*
+* The voltage of the branch of a component with one current, if its equation only refers to the
+* potentials at the ends of the branch and its own current, a * (V(from) - V(to)) + c * I = b.
+*/
+fn impedance(
+    comp: &Component,
+    offset: usize,
+    branch: &MeshBranch,
+    num_nodes: usize,
+    num_variables: usize,
+) -> Result<Option<BranchVoltage>, CircuitError> {
+    if comp.get_currents() != 1 || branch.current != offset || branch.from == branch.to {
+        return Ok(None);
+    }
+    let mut row = vec![0.0; num_variables];
+    let b = comp.equation(offset, &mut row, 0)?;
+    // the reference has no potential in the equation, it is zero
+    let (a, mut allowed) = if branch.from == num_nodes {
+        (-row[branch.to], vec![branch.to, offset])
+    } else {
+        (row[branch.from], vec![branch.from, branch.to, offset])
+    };
+    allowed.sort_unstable();
+    let others = row
+        .iter()
+        .enumerate()
+        .any(|(i, v)| *v != 0.0 && allowed.binary_search(&i).is_err());
+    if a == 0.0 || row[branch.to] != -a || others {
+        return Ok(None);
+    }
+    Ok(Some(BranchVoltage::Impedance {
+        a,
+        b,
+        c: row[offset],
+    }))
+}
+
+/*
+* A spanning tree of the circuit graph and the links that close its loops.
+* parents holds the tree branch and parent of every vertex, None for the reference and vertices
+* that can not be reached, order the vertices in the order they were added to the tree.
+*/
+struct SpanningTree {
+    parents: Vec<Option<(usize, usize)>>,
+    order: Vec<usize>,
+    links: Vec<usize>,
+}
+
+/*
+* A spanning tree grown from the reference, at every step the branch that reaches a new vertex
+* with the lowest kind and depth is added. Voltage defined branches go into the tree first and
+* current defined branches last, keeping the tree shallow keeps the loops short.
+*/
+fn spanning_tree(branches: &[MeshBranch], reference: usize, num_vertices: usize) -> SpanningTree {
+    let mut adjacent = vec![Vec::new(); num_vertices];
+    for (b, branch) in branches.iter().enumerate() {
+        adjacent[branch.from].push((b, branch.to));
+        adjacent[branch.to].push((b, branch.from));
+    }
+    let priority = |b: usize| match branches[b].kind {
+        BranchKind::VoltageDefined => 0,
+        BranchKind::Conductive => 1,
+        BranchKind::CurrentDefined => 2,
+    };
+
+    let mut parents = vec![None; num_vertices];
+    let mut in_tree = vec![false; num_vertices];
+    let mut tree_branch = vec![false; branches.len()];
+    let mut order = Vec::with_capacity(num_vertices);
+    // (kind, depth, tree branch and parent, vertex)
+    let mut frontier = BinaryHeap::from([Reverse((0, 0, None, reference))]);
+    while let Some(Reverse((_, depth, edge, v))) = frontier.pop() {
+        if in_tree[v] {
+            continue;
+        }
+        in_tree[v] = true;
+        parents[v] = edge;
+        order.push(v);
+        if let Some((b, _)) = edge {
+            tree_branch[b] = true;
+        }
+        for &(b, next) in adjacent[v].iter() {
+            if !in_tree[next] {
+                frontier.push(Reverse((priority(b), depth + 1, Some((b, v)), next)));
+            }
+        }
+    }
+    let links = (0..branches.len()).filter(|&b| !tree_branch[b]).collect();
+    SpanningTree {
+        parents,
+        order,
+        links,
+    }
+}
+
+/*
+* The loop closed by a link, every branch on it with +1 where the loop runs along the branch.
+* The loop runs through the link from its from to its to vertex and back through the tree,
+* which fails if the tree does not connect the two vertices.
+*/
+fn fundamental_loop(
+    branches: &[MeshBranch],
+    parents: &[Option<(usize, usize)>],
+    link: usize,
+) -> Result<Vec<(usize, f64)>, CircuitError> {
+    let path_up = |mut v: usize| {
+        let mut path = vec![v];
+        while let Some((_, parent)) = parents[v] {
+            path.push(parent);
+            v = parent;
+        }
+        path
+    };
+    let up_from_to = path_up(branches[link].to);
+    let up_from_from = path_up(branches[link].from);
+    // drop the common part above the vertex where the paths meet
+    let common = up_from_to
+        .iter()
+        .rev()
+        .zip(up_from_from.iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    // every vertex below the meeting point has a parent, unless the paths never meet
+    let parent_branch = |v: usize| {
+        parents[v].map(|(b, _)| b).ok_or_else(|| {
+            CircuitError::InvalidParameter(format!(
+                "mesh analysis found no path through the tree between the ends of branch {link}"
+            ))
+        })
+    };
+    let mut res = vec![(link, 1.0)];
+    // up from the to vertex, each step goes from a vertex to its parent
+    for &v in &up_from_to[..up_from_to.len() - common] {
+        let b = parent_branch(v)?;
+        res.push((b, if branches[b].from == v { 1.0 } else { -1.0 }));
+    }
+    // down to the from vertex, each step goes from a parent to the vertex
+    for &v in up_from_from[..up_from_from.len() - common].iter().rev() {
+        let b = parent_branch(v)?;
+        res.push((b, if branches[b].to == v { 1.0 } else { -1.0 }));
+    }
+    Ok(res)
+}
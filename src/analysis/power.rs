@@ -0,0 +1,72 @@
+use serde::{Deserialize, Serialize};
+
+use crate::components::Component;
+use crate::error::CircuitError;
+use crate::solution::Solution;
+
+// the largest relative difference between sourced and dissipated power of a balanced circuit
+const BALANCE_TOLERANCE: f64 = 1e-6;
+
+/// The power absorbed by every component of a solved circuit, in the order of the components.
+/// A negative power means the component delivers power to the rest of the circuit.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PowerReport {
+    pub components: Vec<(String, f64)>,
+    /// the power delivered by the components with a negative power
+    pub sourced: f64,
+    /// the power absorbed by the components with a positive power
+    pub dissipated: f64,
+}
+
+impl PowerReport {
+    /// What is left of the sourced power after subtracting the dissipated power, zero if
+    /// power is conserved.
+    pub fn balance(&self) -> f64 {
+        self.sourced - self.dissipated
+    }
+
+    /// Checks that the sourced and dissipated power agree up to rounding, a mismatch means
+    /// a component stamps its current with the wrong sign.
+    pub fn is_balanced(&self) -> bool {
+        let scale = self.sourced.max(self.dissipated);
+        self.balance().abs() <= BALANCE_TOLERANCE * scale + f64::EPSILON
+    }
+}
+
+/*
+* this impl block contains the power analysis of a solved circuit.
+*/
+impl Solution {
+    /// The power absorbed by the component, the sum of the pin potentials times the currents
+    /// flowing into the component through them. For a resistor this is I²R, for a source it is
+    /// negative while it delivers power.
+    pub fn power(&self, component: &str) -> Result<f64, CircuitError> {
+        self.component_power(self.component(component)?)
+    }
+
+    fn component_power(&self, component: &Component) -> Result<f64, CircuitError> {
+        let mut power = 0.0;
+        for &pin in component.get_connection_types() {
+            power += self.pin_potential(component, pin)? * self.pin_current(component, pin)?;
+        }
+        Ok(power)
+    }
+
+    pub fn power_report(&self) -> Result<PowerReport, CircuitError> {
+        let mut report = PowerReport {
+            components: Vec::with_capacity(self.circuit().components.len()),
+            sourced: 0.0,
+            dissipated: 0.0,
+        };
+        for comp in self.circuit().components.iter() {
+            let power = self.component_power(comp)?;
+            if power < 0.0 {
+                report.sourced -= power;
+            } else {
+                report.dissipated += power;
+            }
+            report.components.push((comp.get_name(), power));
+        }
+        Ok(report)
+    }
+}
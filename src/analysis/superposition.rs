@@ -0,0 +1,116 @@
+use serde::{Deserialize, Serialize};
+
+use crate::components::Component::{DCCurrentSourceComponent, DCVoltageSourceComponent};
+use crate::error::CircuitError;
+use crate::solver::Solver;
+
+/// The operating point of a linear circuit split into the contributions of its independent sources.
+/// `potentials[k]` and `currents[k]` hold the node potentials and component currents with only
+/// `sources[k]` switched on, summed over k they give the operating point of the whole circuit.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SuperpositionResult {
+    pub sources: Vec<String>,
+    pub potentials: Vec<Vec<f64>>,
+    pub currents: Vec<Vec<f64>>,
+}
+
+impl SuperpositionResult {
+    pub fn len(&self) -> usize {
+        self.sources.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sources.is_empty()
+    }
+
+    /// The contribution of every source to the potential of a node.
+    pub fn node_contributions(&self, node_id: usize) -> Vec<f64> {
+        self.potentials.iter().map(|p| p[node_id]).collect()
+    }
+
+    /// The contribution of every source to a component current,
+    /// index is relative to the first current.
+    pub fn current_contributions(&self, index: usize) -> Vec<f64> {
+        self.currents.iter().map(|c| c[index]).collect()
+    }
+
+    /// The node potentials with every source switched on, the sum of the contributions.
+    pub fn total_potentials(&self) -> Vec<f64> {
+        sum_columns(&self.potentials)
+    }
+
+    pub fn total_currents(&self) -> Vec<f64> {
+        sum_columns(&self.currents)
+    }
+}
+
+fn sum_columns(rows: &[Vec<f64>]) -> Vec<f64> {
+    let mut total = vec![0.0; rows.first().map_or(0, Vec::len)];
+    for row in rows {
+        for (t, v) in total.iter_mut().zip(row) {
+            *t += v;
+        }
+    }
+    total
+}
+
+/*
+* this impl block contains the superposition analysis of a circuit.
+*/
+impl Solver {
+    /// Solves the circuit once for every independent source with all other independent sources
+    /// set to zero, voltage sources become shorts and current sources become opens. Controlled
+    /// sources stay in place. Only linear circuits can be split up like this.
+    pub fn superposition(&mut self) -> Result<SuperpositionResult, CircuitError> {
+        if let Some(component) = self.components().iter().find(|c| c.is_nonlinear()) {
+            return Err(CircuitError::InvalidParameter(format!(
+                "superposition needs a linear circuit but {} is nonlinear",
+                component.get_name()
+            )));
+        }
+        let sources: Vec<usize> = self
+            .components()
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| matches!(c, DCVoltageSourceComponent(_) | DCCurrentSourceComponent(_)))
+            .map(|(comp_id, _)| comp_id)
+            .collect();
+        let originals = sources
+            .iter()
+            .map(|&comp_id| self.circuit.components[comp_id].sweep_value())
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let result = self.run_superposition(&sources, &originals);
+        // the sources get their own values back even if a solve failed
+        for (&comp_id, &value) in sources.iter().zip(&originals) {
+            self.circuit.components[comp_id].set_sweep_value(value)?;
+        }
+        result
+    }
+
+    fn run_superposition(
+        &mut self,
+        sources: &[usize],
+        originals: &[f64],
+    ) -> Result<SuperpositionResult, CircuitError> {
+        let num_nodes = self.nodes().len();
+        let mut result = SuperpositionResult {
+            sources: Vec::with_capacity(sources.len()),
+            potentials: Vec::with_capacity(sources.len()),
+            currents: Vec::with_capacity(sources.len()),
+        };
+        for &active in sources {
+            for (&comp_id, &value) in sources.iter().zip(originals) {
+                let value = if comp_id == active { value } else { 0.0 };
+                self.circuit.components[comp_id].set_sweep_value(value)?;
+            }
+            let mut x = self.solve_system()?;
+            result
+                .sources
+                .push(self.circuit.components[active].get_name());
+            result.currents.push(x.split_off(num_nodes));
+            result.potentials.push(x);
+        }
+        Ok(result)
+    }
+}
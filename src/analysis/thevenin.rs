@@ -0,0 +1,99 @@
+use serde::{Deserialize, Serialize};
+
+use crate::components::{
+    ConnectionType::{Anode, Cathode},
+    DCCurrentSource, DCCurrentSourceComponent, Identifer,
+};
+use crate::error::CircuitError;
+use crate::solver::Solver;
+
+// the current injected into the positive node to measure the resistance between the nodes
+const TEST_CURRENT: f64 = 1e-3;
+
+/// The Thevenin equivalent of a circuit seen from two of its nodes, a voltage source in series
+/// with a resistance. The voltage is V(positive) - V(negative) with nothing connected between
+/// the nodes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TheveninEquivalent {
+    pub positive: String,
+    pub negative: String,
+    pub voltage: f64,
+    pub resistance: f64,
+}
+
+impl TheveninEquivalent {
+    /// The current of the Norton equivalent, the current flowing from the positive to the
+    /// negative node when they are shorted. There is none if the resistance is zero, the
+    /// nodes are then joined by voltage sources or inductors and held at the voltage.
+    pub fn norton_current(&self) -> Option<f64> {
+        (self.resistance != 0.0).then(|| self.voltage / self.resistance)
+    }
+
+    pub fn norton_conductance(&self) -> Option<f64> {
+        (self.resistance != 0.0).then(|| 1.0 / self.resistance)
+    }
+}
+
+/*
+* this impl block contains the Thevenin and Norton equivalents of a circuit.
+*/
+impl Solver {
+    /// The Thevenin equivalent between two nodes of the circuit.
+    /// The voltage is solved with the nodes left open, the resistance from how much that voltage
+    /// rises when a test current is injected into the positive node and taken out of the
+    /// negative node. The sources of the circuit stay in place, for nonlinear circuits the
+    /// resistance is the one seen around the open circuit operating point. Nodes joined by a path
+    /// of voltage defined branches have a resistance of exactly zero.
+    pub fn thevenin(
+        &mut self,
+        positive: &str,
+        negative: &str,
+    ) -> Result<TheveninEquivalent, CircuitError> {
+        if !self.circuit.is_locked() {
+            return Err(CircuitError::NotLocked);
+        }
+        let positive_id = self.circuit.find_node(positive)?;
+        let negative_id = self.circuit.find_node(negative)?;
+        if positive_id == negative_id {
+            return Err(CircuitError::InvalidParameter(format!(
+                "the Thevenin equivalent needs two different nodes, got {positive} twice"
+            )));
+        }
+        let p = self.circuit.get_potential_index(positive_id);
+        let n = self.circuit.get_potential_index(negative_id);
+
+        let open = self.solve_system()?;
+        let voltage = open[p] - open[n];
+
+        let mut tested = self.circuit.clone();
+        tested.unlock();
+        let id = tested.components.len();
+        let name = format!("Ithevenin{id}");
+        let source = DCCurrentSource::new(
+            Identifer::new(name.clone(), id, name.clone(), name),
+            TEST_CURRENT,
+        );
+        tested.add_component(DCCurrentSourceComponent(source));
+        // the cathode supplies the current to its node
+        tested.connect_node(id, positive_id, Cathode)?;
+        tested.connect_node(id, negative_id, Anode)?;
+        tested.lock()?;
+        let mut loaded = Solver::new(tested);
+        loaded.newton = self.newton;
+        let loaded = loaded.solve_system()?;
+        // the voltage between nodes joined by voltage defined branches does not rise at all, the
+        // difference of the solved potentials would only leave rounding errors
+        let resistance = if self.circuit.voltage_defined_path(positive_id, negative_id) {
+            0.0
+        } else {
+            (loaded[p] - loaded[n] - voltage) / TEST_CURRENT
+        };
+
+        Ok(TheveninEquivalent {
+            positive: positive.to_string(),
+            negative: negative.to_string(),
+            voltage,
+            resistance,
+        })
+    }
+}
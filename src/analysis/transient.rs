@@ -0,0 +1,123 @@
+use serde::{Deserialize, Serialize};
+
+use crate::components::{IntegrationMethod, ReactiveMode};
+use crate::error::CircuitError;
+use crate::solver::Solver;
+
+/// The waveforms of a transient analysis.
+/// `potentials[k]` and `currents[k]` hold the node potentials and component currents at `times[k]`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransientResult {
+    pub method: IntegrationMethod,
+    pub times: Vec<f64>,
+    pub potentials: Vec<Vec<f64>>,
+    pub currents: Vec<Vec<f64>>,
+}
+
+impl TransientResult {
+    fn new(method: IntegrationMethod) -> Self {
+        Self {
+            method,
+            times: Vec::new(),
+            potentials: Vec::new(),
+            currents: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, time: f64, x: &[f64], num_nodes: usize) {
+        self.times.push(time);
+        self.potentials.push(x[..num_nodes].to_vec());
+        self.currents.push(x[num_nodes..].to_vec());
+    }
+
+    pub fn len(&self) -> usize {
+        self.times.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.times.is_empty()
+    }
+
+    /// The potential of a node over time.
+    pub fn node_waveform(&self, node_id: usize) -> Vec<f64> {
+        self.potentials.iter().map(|p| p[node_id]).collect()
+    }
+
+    /// A component current over time, index is relative to the first current (not the first unknown).
+    pub fn current_waveform(&self, index: usize) -> Vec<f64> {
+        self.currents.iter().map(|c| c[index]).collect()
+    }
+}
+
+/*
+* this impl block contains the transient analysis of a circuit.
+*/
+impl Solver {
+    /// Simulates the circuit from t = 0 to t_stop with a fixed time step.
+    /// The initial state is the DC operating point with the initial conditions of capacitors and
+    /// inductors forced where they are given. Every time step the energy storing components are
+    /// replaced by their companion models and the system is rebuilt and solved.
+    pub fn transient(
+        &mut self,
+        t_stop: f64,
+        t_step: f64,
+        method: IntegrationMethod,
+    ) -> Result<TransientResult, CircuitError> {
+        if !(t_step > 0.0 && t_stop > 0.0) {
+            return Err(CircuitError::InvalidParameter(format!(
+                "Invalid transient parameters t_stop: {t_stop} t_step: {t_step}"
+            )));
+        }
+        self.set_reactive_mode(ReactiveMode::InitialCondition);
+        let result = self.run_transient(t_stop, t_step, method);
+        // the components are left in DC mode even if the simulation failed
+        self.set_reactive_mode(ReactiveMode::OperatingPoint);
+        result
+    }
+
+    fn run_transient(
+        &mut self,
+        t_stop: f64,
+        t_step: f64,
+        method: IntegrationMethod,
+    ) -> Result<TransientResult, CircuitError> {
+        let num_nodes = self.nodes().len();
+        let mut result = TransientResult::new(method);
+
+        // t = 0
+        let x = self.solve_system()?;
+        self.accept_step(&x)?;
+        result.push(0.0, &x, num_nodes);
+
+        // the last step is shortened so that the simulation ends at t_stop
+        let steps = (t_stop / t_step - 1e-9).ceil() as usize;
+        let mut time = 0.0;
+        for k in 1..=steps {
+            let next_time = (k as f64 * t_step).min(t_stop);
+            let step = next_time - time;
+            for component in self.circuit.components.iter_mut() {
+                component.begin_step(step, method);
+            }
+            let x = self.solve_system()?;
+            self.accept_step(&x)?;
+            result.push(next_time, &x, num_nodes);
+            time = next_time;
+        }
+        Ok(result)
+    }
+
+    fn set_reactive_mode(&mut self, mode: ReactiveMode) {
+        for component in self.circuit.components.iter_mut() {
+            component.set_reactive_mode(mode);
+        }
+    }
+
+    fn accept_step(&mut self, x: &[f64]) -> Result<(), CircuitError> {
+        let circuit = &mut self.circuit;
+        for comp_id in 0..circuit.components.len() {
+            let offset = circuit.get_current_index(&circuit.components[comp_id])?;
+            circuit.components[comp_id].accept_step(x, offset)?;
+        }
+        Ok(())
+    }
+}
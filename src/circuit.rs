@@ -1,23 +1,27 @@
 use std::collections::HashMap;
 
+use serde::{Deserialize, Serialize};
+
 use crate::components::Component;
 use crate::components::Connection::{Connected, Disconnected};
 use crate::components::ConnectionType;
 use crate::components::Identifer;
 use crate::components::Node;
+use crate::error::CircuitError;
 
-/*
-* An id struct that has name, id, components, and nodes.
-*/
-#[derive(Debug, Clone)]
+/// An id struct that has name, id, components, and nodes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Circuit {
     pub name: String,
     pub id: usize,
     pub components: Vec<Component>,
     pub nodes: Vec<Node>,
-    pub locked: bool,
-    pub comp_to_cur_index_map: HashMap<usize, usize>,
-    pub num_variables: usize,
+    locked: bool,
+    // derived from the components when the circuit is locked, so it is not serialized
+    #[serde(skip)]
+    comp_to_cur_index_map: HashMap<usize, usize>,
+    #[serde(skip)]
+    num_variables: usize,
 }
 
 impl Circuit {
@@ -62,10 +66,14 @@ impl Circuit {
         }
     }
 
-    pub fn connect_nodes(&mut self, args: Vec<(usize, usize, ConnectionType)>) {
+    pub fn connect_nodes(
+        &mut self,
+        args: Vec<(usize, usize, ConnectionType)>,
+    ) -> Result<(), CircuitError> {
         for (comp_id, node_id, con_type) in args {
-            self.connect_node(comp_id, node_id, con_type);
+            self.connect_node(comp_id, node_id, con_type)?;
         }
+        Ok(())
     }
 
     pub fn get_potential_index(&self, node_id: usize) -> usize {
@@ -73,51 +81,81 @@ impl Circuit {
         node_id
     }
 
-    pub fn get_current_index(&self, component: &Component) -> usize {
-        self.comp_to_cur_index_map[&component.get_id()]
+    pub fn is_locked(&self) -> bool {
+        self.locked
+    }
+
+    /// Unlocks the circuit so it can be changed, it has to be locked again before it is solved.
+    pub fn unlock(&mut self) {
+        self.locked = false;
+    }
+
+    /// The number of unknowns of the system, the node potentials followed by the component
+    /// currents. It is zero until the circuit is locked.
+    pub fn num_variables(&self) -> usize {
+        self.num_variables
+    }
+
+    pub fn get_current_index(&self, component: &Component) -> Result<usize, CircuitError> {
+        self.comp_to_cur_index_map
+            .get(&component.get_id())
+            .copied()
+            .ok_or(CircuitError::NotLocked)
     }
 
     pub fn connect_components(
         &mut self,
         args: Vec<((usize, ConnectionType), (usize, ConnectionType))>,
-    ) {
+    ) -> Result<(), CircuitError> {
         for (conn1, conn2) in args {
-            self.connect_component(conn1, conn2);
+            self.connect_component(conn1, conn2)?;
         }
+        Ok(())
     }
 
     pub fn connect_component(
         &mut self,
         conn1: (usize, ConnectionType),
         conn2: (usize, ConnectionType),
-    ) {
+    ) -> Result<(), CircuitError> {
         let (comp1_id, con_type1) = conn1;
         let (comp2_id, con_type2) = conn2;
-        assert!(comp1_id < self.components.len());
-        assert!(comp2_id < self.components.len());
         // see if either node is connected already
-        let node1_id = match self.components[comp1_id].get_connection(con_type1) {
+        let node1_id = match self.get_component(comp1_id)?.get_connection(con_type1)? {
             Connected(id, _) => id,
             Disconnected(_) => self.nodes.len(),
         };
-        let node2_id = match self.components[comp2_id].get_connection(con_type2) {
+        let node2_id = match self.get_component(comp2_id)?.get_connection(con_type2)? {
             Connected(id, _) => id,
             Disconnected(_) => self.nodes.len(),
         };
         // See if both nodes are connected already, this means both are connected to a different node
         if node1_id != node2_id && node1_id != self.nodes.len() && node2_id != self.nodes.len() {
-            panic!("Both components are already connected to different nodes");
+            return Err(CircuitError::ConflictingNodes(node1_id, node2_id));
         }
         // the new node is the min number of both nodes, this means that if one of the nodes is not connected yet
         // the new node will be the id of the other node. If neither is connected a new node will be created to connect the two
         let node_id = std::cmp::min(node1_id, node2_id);
 
-        self.connect_node(comp1_id, node_id, con_type1);
-        self.connect_node(comp2_id, node_id, con_type2);
+        self.connect_node(comp1_id, node_id, con_type1)?;
+        self.connect_node(comp2_id, node_id, con_type2)
     }
 
-    pub fn connect_node(&mut self, comp_id: usize, node_id: usize, con_type: ConnectionType) {
-        assert!(comp_id < self.components.len());
+    pub fn connect_node(
+        &mut self,
+        comp_id: usize,
+        node_id: usize,
+        con_type: ConnectionType,
+    ) -> Result<(), CircuitError> {
+        if self.locked {
+            return Err(CircuitError::AlreadyLocked);
+        }
+        // connect the component first, this fails if it does not have the pin
+        let component = self
+            .components
+            .get_mut(comp_id)
+            .ok_or(CircuitError::ComponentOutOfRange(comp_id))?;
+        component.connect(node_id, con_type)?;
 
         while node_id >= self.nodes.len() {
             let identifier = Identifer::from_id(self.nodes.len());
@@ -128,28 +166,103 @@ impl Circuit {
         // select the node and connect it
         let node = &mut self.nodes[node_id];
         node.add_connection(comp_id, con_type);
-        // select the component and connect it
-        let component = &mut self.components[comp_id];
-        component.connect(node_id, con_type)
+        Ok(())
+    }
+
+    /// Connects a pin of the component with the given name to the node with the given name,
+    /// the node is created if there is no node with that name yet.
+    pub fn connect(
+        &mut self,
+        component: &str,
+        con_type: ConnectionType,
+        node: &str,
+    ) -> Result<(), CircuitError> {
+        if self.locked {
+            return Err(CircuitError::AlreadyLocked);
+        }
+        let comp_id = self.find_component(component)?;
+        let node_id = match self.find_node(node) {
+            Ok(node_id) => node_id,
+            Err(_) => self.add_named_node(node),
+        };
+        match self.get_component(comp_id)?.get_connection(con_type)? {
+            Connected(id, _) if id == node_id => Ok(()),
+            Connected(id, _) => Err(CircuitError::ConflictingNodes(id, node_id)),
+            Disconnected(_) => self.connect_node(comp_id, node_id, con_type),
+        }
     }
 
-    pub fn currents_at_node_eq(&self, node_id: usize, eq: &mut [f64]) {
-        let node = &self.nodes[node_id];
+    /// Adds a node with the given name and returns its id.
+    pub fn add_named_node(&mut self, name: &str) -> usize {
+        let id = self.nodes.len();
+        let identifer = Identifer::new(name.to_string(), id, name.to_string(), name.to_string());
+        self.nodes.push(Node::new(identifer));
+        id
+    }
+
+    /// The id of the component with the given name.
+    pub fn find_component(&self, name: &str) -> Result<usize, CircuitError> {
+        self.components
+            .iter()
+            .position(|c| c.get_name() == name)
+            .ok_or_else(|| CircuitError::UnknownComponent(name.to_string()))
+    }
+
+    /// The id of the node with the given name.
+    pub fn find_node(&self, name: &str) -> Result<usize, CircuitError> {
+        self.nodes
+            .iter()
+            .position(|n| n.identifer.name == name)
+            .ok_or_else(|| CircuitError::UnknownNode(name.to_string()))
+    }
+
+    pub fn get_component(&self, comp_id: usize) -> Result<&Component, CircuitError> {
+        self.components
+            .get(comp_id)
+            .ok_or(CircuitError::ComponentOutOfRange(comp_id))
+    }
+
+    pub fn currents_at_node_eq(&self, node_id: usize, eq: &mut [f64]) -> Result<(), CircuitError> {
+        let node = self
+            .nodes
+            .get(node_id)
+            .ok_or(CircuitError::NodeOutOfRange(node_id))?;
 
         for connection in node.connections.iter() {
             // everything whose input is the cathode is added to the current
-            let component_id = connection.get_id();
-            let conn_type = connection.get_connection_type();
-            self.components[component_id].current_representative(
-                self.comp_to_cur_index_map[&component_id],
-                conn_type,
-                eq,
-            );
+            let Connected(component_id, conn_type) = *connection else {
+                continue;
+            };
+            let component = self.get_component(component_id)?;
+            component.current_representative(self.get_current_index(component)?, conn_type, eq)?;
         }
+        Ok(())
     }
 
-    pub fn get_potential(&self, node_id: usize) -> f64 {
-        self.nodes[node_id].potential
+    /// The unknowns the KCL equation of a node can refer to, the currents of its components.
+    pub fn node_columns(&self, node_id: usize) -> Result<Vec<usize>, CircuitError> {
+        let node = self
+            .nodes
+            .get(node_id)
+            .ok_or(CircuitError::NodeOutOfRange(node_id))?;
+
+        let mut columns = Vec::new();
+        for connection in node.connections.iter() {
+            let Connected(component_id, _) = *connection else {
+                continue;
+            };
+            let component = self.get_component(component_id)?;
+            let index = self.get_current_index(component)?;
+            columns.extend(index..index + component.get_currents());
+        }
+        Ok(columns)
+    }
+
+    pub fn get_potential(&self, node_id: usize) -> Result<f64, CircuitError> {
+        self.nodes
+            .get(node_id)
+            .map(|node| node.potential)
+            .ok_or(CircuitError::NodeOutOfRange(node_id))
     }
 
     pub fn add_component(&mut self, component: Component) {
@@ -160,15 +273,25 @@ impl Circuit {
         self.nodes.push(node);
     }
 
-    pub fn lock(&mut self) {
-        assert!(!self.locked, "Attempted to lock a locked circuit");
+    /// Locks the circuit so that it can be solved, this fails if the topology of the circuit
+    /// makes it impossible to solve.
+    pub fn lock(&mut self) -> Result<(), CircuitError> {
+        if self.locked {
+            return Err(CircuitError::AlreadyLocked);
+        }
+        let issues = self.validate();
+        if !issues.is_empty() {
+            return Err(CircuitError::InvalidTopology(issues));
+        }
         self.locked = true;
 
-        self.calc_current_index_map();
+        self.calc_current_index_map()
     }
 
-    pub fn calc_current_index_map(&mut self) {
-        assert!(self.locked);
+    pub fn calc_current_index_map(&mut self) -> Result<(), CircuitError> {
+        if !self.locked {
+            return Err(CircuitError::NotLocked);
+        }
 
         let len = self
             .components
@@ -185,5 +308,20 @@ impl Circuit {
 
         self.comp_to_cur_index_map = res;
         self.num_variables = top_index;
+
+        // current controlled components stamp the current of the component controlling them
+        for comp_id in 0..self.components.len() {
+            let Some(control) = self.components[comp_id].control_component() else {
+                continue;
+            };
+            let control_id = self.find_component(control)?;
+            let control = &self.components[control_id];
+            let (index, sign) = (
+                self.get_current_index(control)?,
+                control.sensed_current_sign(),
+            );
+            self.components[comp_id].set_control_index(index, sign);
+        }
+        Ok(())
     }
 }
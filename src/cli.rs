@@ -0,0 +1,273 @@
+use crate::analysis::{
+    AcResult, DcSweepResult, MeshResult, PowerReport, SuperpositionResult, TheveninEquivalent,
+    TransientResult,
+};
+use crate::components::ConnectionType::*;
+use crate::components::*;
+use crate::error::CircuitError;
+use crate::{json, netlist, Circuit, Solution, Solver};
+
+/// Reads a circuit from a netlist, or from a circuit document if the file ends in .json.
+/// The error is the message to show, a netlist error points at its line in the file.
+pub fn read_circuit(path: &str) -> Result<Circuit, String> {
+    let input = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {path}: {e}"))?;
+    if path.ends_with(".json") {
+        return json::from_json(&input).map_err(|e| format!("Failed to load {path}: {e}"));
+    }
+    netlist::parse(path.to_string(), &input).map_err(|e| {
+        // errors that concern the whole circuit have no line to point at
+        let separator = if e.line == 0 { " " } else { "" };
+        format!("{path}:{separator}{e}")
+    })
+}
+
+fn new_identifer(id: &mut usize, name: &str) -> Identifer {
+    let res = Identifer::new(name.to_string(), *id, name.to_string(), name.to_string());
+    *id += 1;
+    res
+}
+
+/// The circuit solved when no netlist is given: two voltage sources that each feed one of two
+/// resistors selected by a switch.
+pub fn demo_circuit() -> Result<Circuit, CircuitError> {
+    let mut a: usize = 0;
+    let component_id = &mut a;
+
+    let v1 = DCVoltageSource::new(new_identifer(component_id, "V1"), 3.0);
+    let r1 = Resistor::new(new_identifer(component_id, "R1"), 1000.0);
+    let r2 = Resistor::new(new_identifer(component_id, "R2"), 2000.0);
+    let ground = Ground::new(new_identifer(component_id, "GND"));
+    let mut switch1 = SwitchSPDT::new(new_identifer(component_id, "S1"));
+    switch1.toggle(); // optional switch toggle (it works!)
+    let v2 = DCVoltageSource::new(new_identifer(component_id, "V2"), 3.0);
+    let r3 = Resistor::new(new_identifer(component_id, "R3"), 10000.0);
+    let r4 = Resistor::new(new_identifer(component_id, "R4"), 50000.0);
+    let mut switch2 = SwitchSPDT::new(new_identifer(component_id, "S2"));
+    switch2.toggle();
+
+    let components = vec![
+        DCVoltageSourceComponent(v1),
+        ResistorComponent(r1),
+        ResistorComponent(r2),
+        GroundComponent(ground),
+        SwitchSPDTComponent(switch1),
+        DCVoltageSourceComponent(v2),
+        ResistorComponent(r3),
+        ResistorComponent(r4),
+        SwitchSPDTComponent(switch2),
+    ];
+    let mut circuit = Circuit::from_components("test".to_string(), 0, components);
+
+    // (component, connection_type, node)
+    let connections = [
+        ("GND", GroundConnection, "gnd"),
+        ("V1", Anode, "gnd"),
+        ("V1", Cathode, "v1"),
+        ("R1", Anode, "s1_left"),
+        ("R1", Cathode, "gnd"),
+        ("R2", Anode, "s1_right"),
+        ("R2", Cathode, "gnd"),
+        ("S1", Left, "s1_left"),
+        ("S1", Right, "s1_right"),
+        ("S1", Middle, "v1"),
+        ("V2", Anode, "v2"),
+        ("V2", Cathode, "gnd"),
+        ("R3", Anode, "s2_left"),
+        ("R3", Cathode, "v2"),
+        ("R4", Anode, "s2_right"),
+        ("R4", Cathode, "v2"),
+        ("S2", Left, "s2_left"),
+        ("S2", Right, "s2_right"),
+        ("S2", Middle, "gnd"),
+    ];
+    for (component, con_type, node) in connections {
+        circuit.connect(component, con_type, node)?;
+    }
+    circuit.lock()?;
+    Ok(circuit)
+}
+
+/// The system of equations of the operating point as dense rows with the right hand side.
+pub fn format_system(nvm: &Solver) -> Result<String, CircuitError> {
+    let (m, b) = nvm.dense_system()?;
+    let mut out = String::new();
+    for (row, bi) in m.iter().zip(&b) {
+        for v in row {
+            out += &format!("{v:>6} ");
+        }
+        out += &format!("   |    {bi}\n");
+    }
+    Ok(out)
+}
+
+/// The currents of every component followed by the potentials of every node.
+pub fn format_solution(solution: &Solution) -> Result<String, CircuitError> {
+    let circuit = solution.circuit();
+    let mut out = String::new();
+    for comp in circuit.components.iter() {
+        let name = comp.get_name();
+        for (passage, current) in solution.branch_currents(&name)?.iter().enumerate() {
+            out += &format!("Component: {name}.{passage}: {current:.6}A\n");
+        }
+    }
+    for node in circuit.nodes.iter() {
+        let name = node.get_name();
+        out += &format!("Node: {name}: {:.2}V\n", solution.voltage(&name)?);
+    }
+    Ok(out)
+}
+
+/// The power of every component and whether what is sourced matches what is dissipated.
+pub fn format_power(report: &PowerReport) -> String {
+    let mut out = String::new();
+    for (name, power) in report.components.iter() {
+        out += &format!("Power: {name}: {power:.6}W\n");
+    }
+    out += &format!(
+        "Sourced: {:.6}W Dissipated: {:.6}W Balance: {:.3e}W ({})\n",
+        report.sourced,
+        report.dissipated,
+        report.balance(),
+        if report.is_balanced() {
+            "conserved"
+        } else {
+            "NOT conserved"
+        }
+    );
+    out
+}
+
+/// The Thevenin and Norton equivalents between two nodes.
+pub fn format_thevenin(equivalent: &TheveninEquivalent) -> String {
+    let mut out = format!(
+        "Thevenin: {}-{}: {:.6}V in series with {:.6}Ohm\n",
+        equivalent.positive, equivalent.negative, equivalent.voltage, equivalent.resistance
+    );
+    out += &match equivalent.norton_current() {
+        Some(current) => format!(
+            "Norton: {}-{}: {current:.6}A in parallel with {:.6}Ohm\n",
+            equivalent.positive, equivalent.negative, equivalent.resistance
+        ),
+        None => format!(
+            "Norton: {}-{}: none, the nodes are held at a fixed voltage\n",
+            equivalent.positive, equivalent.negative
+        ),
+    };
+    out
+}
+
+/// A table with a column for the contribution of every source and one for their total.
+pub fn format_superposition(nvm: &Solver, result: &SuperpositionResult) -> String {
+    let mut out = format!("{:>12}", "");
+    for source in result.sources.iter() {
+        out += &format!(" {source:>12}");
+    }
+    out += &format!(" {:>12}\n", "total");
+    let mut row = |label: String, contributions: Vec<f64>| {
+        out += &format!("{label:>12}");
+        for value in contributions.iter() {
+            out += &format!(" {value:>12.6}");
+        }
+        out += &format!(" {:>12.6}\n", contributions.iter().sum::<f64>());
+    };
+    for (node_id, node) in nvm.nodes().iter().enumerate() {
+        row(
+            format!("V({})", node.get_name()),
+            result.node_contributions(node_id),
+        );
+    }
+    let mut index = 0;
+    for comp in nvm.components() {
+        for passage in 0..comp.get_currents() {
+            row(
+                format!("I({}.{passage})", comp.get_name()),
+                result.current_contributions(index),
+            );
+            index += 1;
+        }
+    }
+    out
+}
+
+/// The current and path of every mesh, and how far they are from the nodal solution.
+pub fn format_mesh(result: &MeshResult, nodal: &Solution) -> String {
+    let mut out = String::new();
+    for (k, (mesh, current)) in result.loops.iter().zip(&result.mesh_currents).enumerate() {
+        let path: Vec<String> = mesh
+            .components
+            .iter()
+            .map(|(name, direction)| format!("{}{name}", if *direction > 0.0 { "+" } else { "-" }))
+            .collect();
+        out += &format!("Mesh {k}: {current:.6}A through {}\n", path.join(" "));
+    }
+    out += &format!(
+        "Nodal analysis: largest difference {:.3e} ({})\n",
+        result.deviation(nodal),
+        if result.agrees_with(nodal) {
+            "agrees"
+        } else {
+            "DOES NOT agree"
+        }
+    );
+    out
+}
+
+/// A table of the node potentials at every time point.
+pub fn format_waveforms(nvm: &Solver, result: &TransientResult) -> String {
+    let mut out = format!("{:>12}", "time");
+    for node in nvm.nodes() {
+        out += &format!(" {:>12}", format!("V({})", node.get_name()));
+    }
+    out.push('\n');
+    for (time, potentials) in result.times.iter().zip(&result.potentials) {
+        out += &format!("{time:>12.4e}");
+        for potential in potentials {
+            out += &format!(" {potential:>12.6}");
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// A table of the node potentials and component currents at every swept value.
+pub fn format_dc_sweep(nvm: &Solver, result: &DcSweepResult) -> String {
+    let mut out = format!("{:>12}", result.component);
+    for node in nvm.nodes() {
+        out += &format!(" {:>12}", format!("V({})", node.get_name()));
+    }
+    for comp in nvm.components() {
+        for passage in 0..comp.get_currents() {
+            out += &format!(" {:>12}", format!("I({}.{passage})", comp.get_name()));
+        }
+    }
+    out.push('\n');
+    for k in 0..result.len() {
+        out += &format!("{:>12.4e}", result.values[k]);
+        for value in result.potentials[k].iter().chain(&result.currents[k]) {
+            out += &format!(" {value:>12.6}");
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// A table of the magnitude and phase of every node potential at every frequency.
+pub fn format_ac(nvm: &Solver, result: &AcResult) -> String {
+    let mut out = format!("{:>12}", "frequency");
+    for node in nvm.nodes() {
+        out += &format!(" {:>24}", format!("V({}) mag/phase", node.get_name()));
+    }
+    out.push('\n');
+    for (k, frequency) in result.frequencies.iter().enumerate() {
+        out += &format!("{frequency:>12.4e}");
+        for node_id in 0..nvm.nodes().len() {
+            out += &format!(
+                " {:>12.6} {:>10.2}°",
+                result.magnitude(k, node_id),
+                result.phase(k, node_id)
+            );
+        }
+        out.push('\n');
+    }
+    out
+}
@@ -1,23 +1,46 @@
+mod capacitor;
+mod cc_current_source;
+mod cc_voltage_source;
 mod dc_current_source;
 mod dc_voltage_source;
+mod diode;
 mod ground;
+mod inductor;
 mod node;
+mod op_amp;
+mod registry;
 mod resistor;
 mod switch_spdt;
+mod vc_current_source;
+mod vc_voltage_source;
 
+pub use self::capacitor::Capacitor;
+pub use self::cc_current_source::CCCurrentSource;
+pub use self::cc_voltage_source::CCVoltageSource;
 pub use self::dc_current_source::DCCurrentSource;
 pub use self::dc_voltage_source::DCVoltageSource;
+pub use self::diode::Diode;
 pub use self::ground::Ground;
+pub use self::inductor::Inductor;
 pub use self::node::Node;
+pub use self::op_amp::{OpAmp, OpAmpModel, OpAmpRegion};
 pub use self::resistor::Resistor;
 pub use self::switch_spdt::SwitchSPDT;
+pub use self::vc_current_source::VCCurrentSource;
+pub use self::vc_voltage_source::VCVoltageSource;
 pub use self::Component::*;
 pub use self::Connection::*;
+pub use self::{
+    registry::{create_component, register_component, ComponentFactory},
+    switch_spdt::SwitchPosition,
+};
 
-/*
-* An id struct that has name, id, short_name, and long_name.
-*/
-#[derive(Debug, Clone)]
+use crate::error::CircuitError;
+use nalgebra::Complex;
+use serde::{Deserialize, Serialize};
+
+/// An id struct that has name, id, short_name, and long_name.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Identifer {
     pub name: String,
     pub id: usize,
@@ -45,13 +68,13 @@ impl Identifer {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub enum Connection {
     Connected(usize, ConnectionType), // A connection to a node current flows in and out
     Disconnected(ConnectionType),     // A connection to nothing
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum ConnectionType {
     Anode,
     Cathode,
@@ -62,6 +85,8 @@ pub enum ConnectionType {
     //
     Input,
     Output,
+    NonInverting,
+    Inverting,
     Pin,
     Pin0,
     Pin1,
@@ -75,139 +100,408 @@ pub enum ConnectionType {
     Pin9,
 }
 
+/// The integration method used to turn energy storing components into companion models.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum IntegrationMethod {
+    BackwardEuler,
+    Trapezoidal,
+}
+
+/// How an energy storing component is stamped into the system of equations.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum ReactiveMode {
+    #[default]
+    OperatingPoint, // steady state DC, capacitors are open circuits
+    InitialCondition, // start of a transient, initial conditions are forced where given
+    Companion(f64, f64), // a transient time step, (equivalent conductance/resistance, history term)
+}
+
+/// How a component constrains the potentials at the two ends of a branch.
+/// This is used to check the topology of a circuit before it is solved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BranchKind {
+    Conductive,     // current depends on the voltage, eg. a resistor
+    VoltageDefined, // the voltage is fixed regardless of the current, eg. a voltage source
+    CurrentDefined, // the current is fixed regardless of the voltage, eg. a current source
+}
+
+/// A branch between two pins of a component, a from of None is the reference (ground) node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Branch {
+    pub kind: BranchKind,
+    pub from: Option<ConnectionType>,
+    pub to: ConnectionType,
+}
+
+impl Branch {
+    pub const fn new(kind: BranchKind, from: ConnectionType, to: ConnectionType) -> Self {
+        Self {
+            kind,
+            from: Some(from),
+            to,
+        }
+    }
+
+    pub const fn to_reference(kind: BranchKind, to: ConnectionType) -> Self {
+        Self {
+            kind,
+            from: None,
+            to,
+        }
+    }
+}
+
 impl Connection {
-    pub fn get_id(&self) -> usize {
+    pub fn get_id(&self) -> Option<usize> {
         match self {
-            Connected(id, _) => *id,
-            Disconnected(_) => panic!("Disconnected connection has no id"),
+            Connected(id, _) => Some(*id),
+            Disconnected(_) => None,
+        }
+    }
+
+    /// The node a pin of the component identified by identifer is connected to.
+    pub fn node_id(&self, identifer: &Identifer) -> Result<usize, CircuitError> {
+        match self {
+            Connected(id, _) => Ok(*id),
+            Disconnected(connection_type) => {
+                Err(CircuitError::disconnected_pin(identifer, *connection_type))
+            }
         }
     }
 
     pub fn get_connection_type(&self) -> ConnectionType {
         match self {
-            Connected(_, connection_type) => connection_type.clone(),
-            Disconnected(connection_type) => connection_type.clone(),
+            Connected(_, connection_type) => *connection_type,
+            Disconnected(connection_type) => *connection_type,
         }
     }
 
-    pub fn make_disconnect(&mut self) -> Connection {
+    pub fn make_disconnect(&mut self) -> Option<Connection> {
         match self {
             Connected(_, connection_type) => {
-                let connection = Disconnected(connection_type.clone());
-                *self = connection.clone();
-                connection
+                let connection = Disconnected(*connection_type);
+                *self = connection;
+                Some(connection)
             }
-            Disconnected(_) => panic!("Connection already disconnected"),
+            Disconnected(_) => None,
         }
     }
 }
 
-/*
-* this is an enum that contain every possible component
-* that can be in a circuit.
-*/
-#[derive(Debug, Clone)]
+/// this is an enum that contain every possible component
+/// that can be in a circuit. The built in components have their own variant so they can be
+/// matched on, CustomComponent holds components defined outside of this crate.
+#[allow(clippy::enum_variant_names)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
 pub enum Component {
     ResistorComponent(Resistor),
     DCVoltageSourceComponent(DCVoltageSource),
     GroundComponent(Ground),
     DCCurrentSourceComponent(DCCurrentSource),
     SwitchSPDTComponent(SwitchSPDT),
-
-    // This is not meant to be used but it serves as a reminder to always have
-    // a catch all for all match statements.
-    // This should enum never appear anywhere else in the code
-    UnimplementedComponent,
+    CapacitorComponent(Capacitor),
+    InductorComponent(Inductor),
+    DiodeComponent(Diode),
+    VCVoltageSourceComponent(VCVoltageSource),
+    VCCurrentSourceComponent(VCCurrentSource),
+    CCVoltageSourceComponent(CCVoltageSource),
+    CCCurrentSourceComponent(CCCurrentSource),
+    OpAmpComponent(OpAmp),
+    #[serde(with = "registry::custom_component")]
+    CustomComponent(Box<dyn ComponentTrait>),
 }
 
-pub trait ComponentTrait {
+/// Everything the solver needs to know about a component.
+/// Only the methods without a default have to be implemented, the defaults describe a linear
+/// component without a value to sweep, that is not controlled by a current and stores no energy.
+pub trait ComponentTrait: std::fmt::Debug + ComponentClone {
     fn get_id(&self) -> usize;
     fn get_name(&self) -> String;
-    fn connect(&mut self, node: usize, connection_type: ConnectionType);
-    fn disconnect(&mut self, connection_type: ConnectionType);
-    fn get_connection(&self, connection_type: ConnectionType) -> Connection;
-    fn current_representative(&self, index: usize, conn_type: ConnectionType, eq: &mut [f64]);
+    fn connect(&mut self, node: usize, connection_type: ConnectionType)
+        -> Result<(), CircuitError>;
+    fn disconnect(&mut self, connection_type: ConnectionType) -> Result<(), CircuitError>;
+    fn get_connection(&self, connection_type: ConnectionType) -> Result<Connection, CircuitError>;
+    /// The pins of the component, in the order their nodes are given in a netlist.
+    fn connection_types(&self) -> &'static [ConnectionType];
+    /// The branches of the component in a DC operating point.
+    fn branches(&self) -> Vec<Branch>;
+    fn current_representative(
+        &self,
+        index: usize,
+        conn_type: ConnectionType,
+        eq: &mut [f64],
+    ) -> Result<(), CircuitError>;
+    /// The number of equations, which is also the number of currents of the component.
     fn num_eq(&self) -> usize;
-    fn equation(&self, offset: usize, equation: &mut [f64], eq_id: usize) -> f64;
+    /// Writes equation eq_id of the component into a zeroed row of the system and returns its
+    /// right hand side. offset is the index of the first current of the component. Only the
+    /// columns of the nodes the pins are connected to, the currents of the component
+    /// (offset..offset + num_eq) and the controlling current may be written, an entry in any
+    /// other column fails the solve with InvalidStamp. The same holds for ac_equation.
+    fn equation(
+        &self,
+        offset: usize,
+        equation: &mut [f64],
+        eq_id: usize,
+    ) -> Result<f64, CircuitError>;
+    fn ac_equation(
+        &self,
+        offset: usize,
+        equation: &mut [Complex<f64>],
+        eq_id: usize,
+        omega: f64,
+    ) -> Result<Complex<f64>, CircuitError>;
+
+    /// The type name a custom component is registered under and the parameters its factory
+    /// is called with, used to save the component. Built in components are not registered.
+    fn registered_type(&self) -> Option<&str> {
+        None
+    }
+
+    fn parameters(&self) -> Vec<(String, f64)> {
+        Vec::new()
+    }
+
+    /// The value a DC sweep varies.
+    fn sweep_value(&self) -> Result<f64, CircuitError> {
+        Err(CircuitError::InvalidParameter(format!(
+            "{} has no value that can be swept",
+            self.get_name()
+        )))
+    }
+
+    fn set_sweep_value(&mut self, _value: f64) -> Result<(), CircuitError> {
+        Err(CircuitError::InvalidParameter(format!(
+            "{} has no value that can be swept",
+            self.get_name()
+        )))
+    }
+
+    /// The name of the component whose current controls this component, if any.
+    fn control_component(&self) -> Option<&str> {
+        None
+    }
+
+    fn control_index(&self) -> Option<usize> {
+        None
+    }
+
+    /// Sets the index of the controlling current and the sign it is sensed with (see
+    /// sensed_current_sign of the controlling component), this is done when the circuit is locked.
+    fn set_control_index(&mut self, _index: usize, _sign: f64) {}
+
+    /// The sign a current controlled source senses the current of this component with, relative
+    /// to the current in its unknown. A voltage source is sensed like I(V) in SPICE, from its
+    /// positive node (the cathode) through it to its negative node, the opposite of its current.
+    fn sensed_current_sign(&self) -> f64 {
+        1.0
+    }
+
+    /// Whether the component has to be re-linearized every Newton-Raphson iteration.
+    fn is_nonlinear(&self) -> bool {
+        false
+    }
+
+    /// Re-linearizes the component around the solution x of a Newton-Raphson iteration.
+    /// offset is the index of the first current of the component in x.
+    /// Returns true if the step was limited, the iteration has not converged then.
+    fn update_operating_point(&mut self, _x: &[f64], _offset: usize) -> Result<bool, CircuitError> {
+        Ok(false)
+    }
+
+    /// Sets how an energy storing component is stamped.
+    fn set_reactive_mode(&mut self, _mode: ReactiveMode) {}
+
+    /// Prepares an energy storing component for a transient time step of length step.
+    fn begin_step(&mut self, _step: f64, _method: IntegrationMethod) {}
+
+    /// Stores the state of an energy storing component from the solution x of an accepted time point.
+    /// offset is the index of the first current of the component in x.
+    fn accept_step(&mut self, _x: &[f64], _offset: usize) -> Result<(), CircuitError> {
+        Ok(())
+    }
+}
+
+/// Lets boxed components be cloned, it is implemented for every component that is Clone.
+pub trait ComponentClone {
+    fn clone_box(&self) -> Box<dyn ComponentTrait>;
+}
+
+impl<T: ComponentTrait + Clone + 'static> ComponentClone for T {
+    fn clone_box(&self) -> Box<dyn ComponentTrait> {
+        Box::new(self.clone())
+    }
+}
+
+impl Clone for Box<dyn ComponentTrait> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
 }
 
 impl Component {
-    pub const fn get_currents(&self) -> usize {
-        // &'static [ConnectionType] {
-        // currents
-        // "rank" in the matrix required to calculate component
-        // use ConnectionType::*;
+    /// The component behind the variant, every method below dispatches through it.
+    pub fn as_trait(&self) -> &dyn ComponentTrait {
         match self {
-            ResistorComponent(_) => 1,        // &[Cathode],
-            DCVoltageSourceComponent(_) => 1, // &[Cathode],
-            GroundComponent(_) => 1,          // &[Cathode],
-            DCCurrentSourceComponent(_) => 1, // &[Cathode],
-            SwitchSPDTComponent(_) => 2,      // &[Output1, Output2],
-            _ => 0,                           // &[],
+            ResistorComponent(resistor) => resistor,
+            DCVoltageSourceComponent(dc_vs) => dc_vs,
+            GroundComponent(ground) => ground,
+            DCCurrentSourceComponent(dc_cs) => dc_cs,
+            SwitchSPDTComponent(switch) => switch,
+            CapacitorComponent(capacitor) => capacitor,
+            InductorComponent(inductor) => inductor,
+            DiodeComponent(diode) => diode,
+            VCVoltageSourceComponent(vcvs) => vcvs,
+            VCCurrentSourceComponent(vccs) => vccs,
+            CCVoltageSourceComponent(ccvs) => ccvs,
+            CCCurrentSourceComponent(cccs) => cccs,
+            OpAmpComponent(op_amp) => op_amp,
+            CustomComponent(custom) => custom.as_ref(),
         }
     }
 
-    pub fn get_id(&self) -> usize {
+    pub fn as_trait_mut(&mut self) -> &mut dyn ComponentTrait {
         match self {
-            ResistorComponent(resistor) => resistor.get_id(),
-            DCVoltageSourceComponent(dc_vs) => dc_vs.get_id(),
-            GroundComponent(ground) => ground.get_id(),
-            DCCurrentSourceComponent(dc_cs) => dc_cs.get_id(),
-            SwitchSPDTComponent(switch) => switch.get_id(),
-            _ => panic!("get_id not implemented for {self:?}"),
+            ResistorComponent(resistor) => resistor,
+            DCVoltageSourceComponent(dc_vs) => dc_vs,
+            GroundComponent(ground) => ground,
+            DCCurrentSourceComponent(dc_cs) => dc_cs,
+            SwitchSPDTComponent(switch) => switch,
+            CapacitorComponent(capacitor) => capacitor,
+            InductorComponent(inductor) => inductor,
+            DiodeComponent(diode) => diode,
+            VCVoltageSourceComponent(vcvs) => vcvs,
+            VCCurrentSourceComponent(vccs) => vccs,
+            CCVoltageSourceComponent(ccvs) => ccvs,
+            CCCurrentSourceComponent(cccs) => cccs,
+            OpAmpComponent(op_amp) => op_amp,
+            CustomComponent(custom) => custom.as_mut(),
         }
     }
+
+    /// The number of currents of the component, its "rank" in the matrix.
+    pub fn get_currents(&self) -> usize {
+        self.as_trait().num_eq()
+    }
+
+    pub fn get_connection_types(&self) -> &'static [ConnectionType] {
+        self.as_trait().connection_types()
+    }
+
+    pub fn get_branches(&self) -> Vec<Branch> {
+        self.as_trait().branches()
+    }
+
+    pub fn get_id(&self) -> usize {
+        self.as_trait().get_id()
+    }
+
     pub fn get_name(&self) -> String {
-        match self {
-            ResistorComponent(resistor) => resistor.identifer.name.clone(),
-            DCVoltageSourceComponent(dc_vs) => dc_vs.identifer.name.clone(),
-            GroundComponent(ground) => ground.identifer.name.clone(),
-            DCCurrentSourceComponent(dc_cs) => dc_cs.identifer.name.clone(),
-            SwitchSPDTComponent(switch) => switch.identifer.name.clone(),
-            _ => panic!("get_name not implemented for {self:?}"),
-        }
+        self.as_trait().get_name()
     }
-    pub fn connect(&mut self, node: usize, connection_type: ConnectionType) {
-        match self {
-            ResistorComponent(resistor) => resistor.connect(node, connection_type),
-            DCVoltageSourceComponent(dc_vs) => dc_vs.connect(node, connection_type),
-            GroundComponent(ground) => ground.connect(node, connection_type),
-            DCCurrentSourceComponent(dc_cs) => dc_cs.connect(node, connection_type),
-            SwitchSPDTComponent(switch) => switch.connect(node, connection_type),
-            _ => panic!("connect not implemented for {self:?}"),
-        }
+
+    pub fn connect(
+        &mut self,
+        node: usize,
+        connection_type: ConnectionType,
+    ) -> Result<(), CircuitError> {
+        self.as_trait_mut().connect(node, connection_type)
     }
 
-    pub fn get_connection(&self, connection_type: ConnectionType) -> Connection {
-        match self {
-            ResistorComponent(resistor) => resistor.get_connection(connection_type),
-            DCVoltageSourceComponent(dc_vs) => dc_vs.get_connection(connection_type),
-            GroundComponent(ground) => ground.get_connection(connection_type),
-            DCCurrentSourceComponent(dc_cs) => dc_cs.get_connection(connection_type),
-            SwitchSPDTComponent(switch) => switch.get_connection(connection_type),
-            _ => panic!("get_connection not implemented for {self:?}"),
-        }
+    pub fn get_connection(
+        &self,
+        connection_type: ConnectionType,
+    ) -> Result<Connection, CircuitError> {
+        self.as_trait().get_connection(connection_type)
     }
 
-    pub fn equation(&self, offset: usize, equation: &mut [f64], eq_id: usize) -> f64 {
-        match self {
-            ResistorComponent(resistor) => resistor.equation(offset, equation, eq_id),
-            DCVoltageSourceComponent(dc_vs) => dc_vs.equation(offset, equation, eq_id),
-            GroundComponent(ground) => ground.equation(offset, equation, eq_id),
-            DCCurrentSourceComponent(dc_cs) => dc_cs.equation(offset, equation, eq_id),
-            SwitchSPDTComponent(switch) => switch.equation(offset, equation, eq_id),
-            _ => panic!("equation not implemented for {self:?}"),
-        }
+    pub fn equation(
+        &self,
+        offset: usize,
+        equation: &mut [f64],
+        eq_id: usize,
+    ) -> Result<f64, CircuitError> {
+        self.as_trait().equation(offset, equation, eq_id)
     }
 
-    pub fn current_representative(&self, index: usize, conn_type: ConnectionType, eq: &mut [f64]) {
-        match self {
-            ResistorComponent(resistor) => resistor.current_representative(index, conn_type, eq),
-            DCVoltageSourceComponent(dc_vs) => dc_vs.current_representative(index, conn_type, eq),
-            GroundComponent(ground) => ground.current_representative(index, conn_type, eq),
-            DCCurrentSourceComponent(dc_cs) => dc_cs.current_representative(index, conn_type, eq),
-            SwitchSPDTComponent(switch) => switch.current_representative(index, conn_type, eq),
-            _ => panic!("current_representative not implemented for {self:?}"),
-        }
+    pub fn ac_equation(
+        &self,
+        offset: usize,
+        equation: &mut [Complex<f64>],
+        eq_id: usize,
+        omega: f64,
+    ) -> Result<Complex<f64>, CircuitError> {
+        self.as_trait().ac_equation(offset, equation, eq_id, omega)
+    }
+
+    pub fn current_representative(
+        &self,
+        index: usize,
+        conn_type: ConnectionType,
+        eq: &mut [f64],
+    ) -> Result<(), CircuitError> {
+        self.as_trait().current_representative(index, conn_type, eq)
+    }
+
+    pub fn sweep_value(&self) -> Result<f64, CircuitError> {
+        self.as_trait().sweep_value()
+    }
+
+    pub fn set_sweep_value(&mut self, value: f64) -> Result<(), CircuitError> {
+        self.as_trait_mut().set_sweep_value(value)
+    }
+
+    pub fn control_component(&self) -> Option<&str> {
+        self.as_trait().control_component()
+    }
+
+    pub fn control_index(&self) -> Option<usize> {
+        self.as_trait().control_index()
+    }
+
+    /// The unknowns the equations of the component can refer to: the nodes it is connected to,
+    /// its own currents starting at offset and the current controlling it.
+    pub fn stamp_columns(&self, offset: usize) -> Vec<usize> {
+        let mut columns: Vec<usize> = self
+            .get_connection_types()
+            .iter()
+            .filter_map(|pin| self.get_connection(*pin).ok()?.get_id())
+            .collect();
+        columns.extend(offset..offset + self.get_currents());
+        columns.extend(self.control_index());
+        columns
+    }
+
+    pub fn set_control_index(&mut self, index: usize, sign: f64) {
+        self.as_trait_mut().set_control_index(index, sign)
+    }
+
+    pub fn sensed_current_sign(&self) -> f64 {
+        self.as_trait().sensed_current_sign()
+    }
+
+    pub fn is_nonlinear(&self) -> bool {
+        self.as_trait().is_nonlinear()
+    }
+
+    pub fn update_operating_point(
+        &mut self,
+        x: &[f64],
+        offset: usize,
+    ) -> Result<bool, CircuitError> {
+        self.as_trait_mut().update_operating_point(x, offset)
+    }
+
+    pub fn set_reactive_mode(&mut self, mode: ReactiveMode) {
+        self.as_trait_mut().set_reactive_mode(mode)
+    }
+
+    pub fn begin_step(&mut self, step: f64, method: IntegrationMethod) {
+        self.as_trait_mut().begin_step(step, method)
+    }
+
+    pub fn accept_step(&mut self, x: &[f64], offset: usize) -> Result<(), CircuitError> {
+        self.as_trait_mut().accept_step(x, offset)
     }
 }
@@ -0,0 +1,229 @@
+use nalgebra::Complex;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    Branch,
+    BranchKind::CurrentDefined,
+    ComponentTrait, Connection,
+    Connection::*,
+    ConnectionType::{self, *},
+    Identifer, IntegrationMethod, ReactiveMode,
+};
+use crate::error::CircuitError;
+
+/// This struct represents a capacitor in a circuit
+/// It has an identifer, capacitance, an optional initial voltage and two nodes.
+/// voltage and current hold the state of the last accepted time step during transient analysis,
+/// like the mode they are solver state and are not saved.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Capacitor {
+    pub identifer: Identifer,
+    pub capacitance: f64,
+    pub initial_voltage: Option<f64>,
+    pub node1: Connection,
+    pub node2: Connection,
+    #[serde(skip)]
+    pub mode: ReactiveMode,
+    #[serde(skip)]
+    pub voltage: f64,
+    #[serde(skip)]
+    pub current: f64,
+}
+
+impl std::fmt::Debug for Capacitor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "C ({}) C:{} IC:{:?} Node1: {:?} Node2: {:?}",
+            self.identifer.id, self.capacitance, self.initial_voltage, self.node1, self.node2
+        )
+    }
+}
+
+impl Capacitor {
+    pub fn new(identifer: Identifer, capacitance: f64) -> Self {
+        Self {
+            identifer,
+            capacitance,
+            initial_voltage: None,
+            node1: Disconnected(Anode),
+            node2: Disconnected(Cathode),
+            mode: ReactiveMode::OperatingPoint,
+            voltage: 0.0,
+            current: 0.0,
+        }
+    }
+
+    pub fn set_initial_voltage(&mut self, voltage: f64) {
+        self.initial_voltage = Some(voltage);
+    }
+
+    pub fn set_mode(&mut self, mode: ReactiveMode) {
+        self.mode = mode;
+    }
+
+    /// The admittance of the capacitor at the angular frequency omega, Y = jwC.
+    pub fn admittance(&self, omega: f64) -> Complex<f64> {
+        Complex::new(0.0, omega * self.capacitance)
+    }
+
+    /// Prepares the companion model for a time step of length step using the stored state.
+    /// Backward euler:  i = C/h * (v - v_prev)
+    /// Trapezoidal:     i = 2C/h * (v - v_prev) - i_prev
+    pub fn prepare_companion(&mut self, step: f64, method: IntegrationMethod) {
+        let (conductance, history) = match method {
+            IntegrationMethod::BackwardEuler => {
+                let g = self.capacitance / step;
+                (g, g * self.voltage)
+            }
+            IntegrationMethod::Trapezoidal => {
+                let g = 2.0 * self.capacitance / step;
+                (g, g * self.voltage + self.current)
+            }
+        };
+        self.mode = ReactiveMode::Companion(conductance, history);
+    }
+
+    /// Stores the voltage and current of an accepted time point as the state of the next step.
+    pub fn accept(&mut self, voltage: f64, current: f64) {
+        self.voltage = voltage;
+        self.current = current;
+    }
+}
+
+impl ComponentTrait for Capacitor {
+    fn get_id(&self) -> usize {
+        self.identifer.id
+    }
+
+    fn get_name(&self) -> String {
+        self.identifer.name.clone()
+    }
+
+    fn connect(
+        &mut self,
+        node: usize,
+        connection_type: ConnectionType,
+    ) -> Result<(), CircuitError> {
+        match connection_type {
+            Anode => self.node1 = Connected(node, Anode),
+            Cathode => self.node2 = Connected(node, Cathode),
+            _ => return Err(CircuitError::invalid_pin(&self.identifer, connection_type)),
+        };
+        Ok(())
+    }
+
+    fn disconnect(&mut self, connection_type: ConnectionType) -> Result<(), CircuitError> {
+        match connection_type {
+            Anode => self.node1 = Disconnected(Anode),
+            Cathode => self.node2 = Disconnected(Cathode),
+            _ => return Err(CircuitError::invalid_pin(&self.identifer, connection_type)),
+        };
+        Ok(())
+    }
+
+    fn get_connection(&self, connection_type: ConnectionType) -> Result<Connection, CircuitError> {
+        match connection_type {
+            Anode => Ok(self.node1),
+            Cathode => Ok(self.node2),
+            _ => Err(CircuitError::invalid_pin(&self.identifer, connection_type)),
+        }
+    }
+
+    fn current_representative(
+        &self,
+        index: usize,
+        conn_type: ConnectionType,
+        eq: &mut [f64],
+    ) -> Result<(), CircuitError> {
+        match conn_type {
+            Anode => eq[index] = -1.0,  // current flows out of the node
+            Cathode => eq[index] = 1.0, // current flows into the node
+            _ => return Err(CircuitError::invalid_pin(&self.identifer, conn_type)),
+        };
+        Ok(())
+    }
+
+    fn num_eq(&self) -> usize {
+        1
+    }
+
+    fn equation(
+        &self,
+        offset: usize,
+        equation: &mut [f64],
+        eq_id: usize,
+    ) -> Result<f64, CircuitError> {
+        if eq_id >= self.num_eq() {
+            return Err(CircuitError::invalid_equation(&self.identifer, eq_id));
+        }
+        let v1 = self.node1.node_id(&self.identifer)?;
+        let v2 = self.node2.node_id(&self.identifer)?;
+        match (self.mode, self.initial_voltage) {
+            (ReactiveMode::Companion(g, history), _) => {
+                // I = G(V1 - V2) - I_hist
+                // G V1 - G V2 - I = I_hist
+                equation[v1] = g;
+                equation[v2] = -g;
+                equation[offset] = -1.0;
+                Ok(history)
+            }
+            (ReactiveMode::InitialCondition, Some(voltage)) => {
+                // V1 - V2 = V_ic
+                equation[v1] = 1.0;
+                equation[v2] = -1.0;
+                Ok(voltage)
+            }
+            _ => {
+                // open circuit, I = 0
+                equation[offset] = 1.0;
+                Ok(0.0)
+            }
+        }
+    }
+
+    fn ac_equation(
+        &self,
+        offset: usize,
+        equation: &mut [Complex<f64>],
+        eq_id: usize,
+        omega: f64,
+    ) -> Result<Complex<f64>, CircuitError> {
+        // I = jwC (V1 - V2)
+        // jwC V1 - jwC V2 - I = 0
+        if eq_id >= self.num_eq() {
+            return Err(CircuitError::invalid_equation(&self.identifer, eq_id));
+        }
+        let v1 = self.node1.node_id(&self.identifer)?;
+        let v2 = self.node2.node_id(&self.identifer)?;
+        let y = self.admittance(omega);
+        equation[v1] = y;
+        equation[v2] = -y;
+        equation[offset] = Complex::from(-1.0);
+        Ok(Complex::from(0.0))
+    }
+
+    fn connection_types(&self) -> &'static [ConnectionType] {
+        &[Anode, Cathode]
+    }
+
+    fn branches(&self) -> Vec<Branch> {
+        // a capacitor is open in DC
+        vec![Branch::new(CurrentDefined, Anode, Cathode)]
+    }
+
+    fn set_reactive_mode(&mut self, mode: ReactiveMode) {
+        self.set_mode(mode);
+    }
+
+    fn begin_step(&mut self, step: f64, method: IntegrationMethod) {
+        self.prepare_companion(step, method);
+    }
+
+    fn accept_step(&mut self, x: &[f64], offset: usize) -> Result<(), CircuitError> {
+        let v1 = self.node1.node_id(&self.identifer)?;
+        let v2 = self.node2.node_id(&self.identifer)?;
+        self.accept(x[v1] - x[v2], x[offset]);
+        Ok(())
+    }
+}
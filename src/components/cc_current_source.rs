@@ -0,0 +1,172 @@
+use nalgebra::Complex;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    Branch,
+    BranchKind::CurrentDefined,
+    ComponentTrait, Connection,
+    Connection::*,
+    ConnectionType::{self, *},
+    Identifer,
+};
+use crate::error::CircuitError;
+
+/// This struct represents a current controlled current source (SPICE F element) in a circuit.
+/// The current flowing into Pin0 (out+), through the source and out of Pin1 (out-) is gain times
+/// the current of the component named control, that is the current flowing from its anode through
+/// it to its cathode, or for a voltage source the SPICE current I(V) from its positive node through it.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CCCurrentSource {
+    pub identifer: Identifer,
+    pub gain: f64,
+    pub control: String,
+    #[serde(skip)]
+    pub control_index: Option<usize>,
+    #[serde(skip)]
+    pub control_sign: f64,
+    pub out_pos: Connection,
+    pub out_neg: Connection,
+}
+
+impl std::fmt::Debug for CCCurrentSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "F ({}) gain:{} Control: {} Out+: {:?} Out-: {:?}",
+            self.identifer.id, self.gain, self.control, self.out_pos, self.out_neg
+        )
+    }
+}
+
+impl CCCurrentSource {
+    pub fn new(identifer: Identifer, gain: f64, control: String) -> Self {
+        Self {
+            identifer,
+            gain,
+            control,
+            control_index: None,
+            control_sign: 1.0,
+            out_pos: Disconnected(Pin0),
+            out_neg: Disconnected(Pin1),
+        }
+    }
+
+    // the index and sign of the controlling current are set when the circuit is locked
+    fn locked_control(&self) -> Result<(usize, f64), CircuitError> {
+        let index = self.control_index.ok_or(CircuitError::NotLocked)?;
+        Ok((index, self.control_sign))
+    }
+}
+
+impl ComponentTrait for CCCurrentSource {
+    fn get_id(&self) -> usize {
+        self.identifer.id
+    }
+
+    fn get_name(&self) -> String {
+        self.identifer.name.clone()
+    }
+
+    fn connect(
+        &mut self,
+        node: usize,
+        connection_type: ConnectionType,
+    ) -> Result<(), CircuitError> {
+        match connection_type {
+            Pin0 => self.out_pos = Connected(node, Pin0),
+            Pin1 => self.out_neg = Connected(node, Pin1),
+            _ => return Err(CircuitError::invalid_pin(&self.identifer, connection_type)),
+        };
+        Ok(())
+    }
+
+    fn disconnect(&mut self, connection_type: ConnectionType) -> Result<(), CircuitError> {
+        match connection_type {
+            Pin0 => self.out_pos = Disconnected(Pin0),
+            Pin1 => self.out_neg = Disconnected(Pin1),
+            _ => return Err(CircuitError::invalid_pin(&self.identifer, connection_type)),
+        };
+        Ok(())
+    }
+
+    fn get_connection(&self, connection_type: ConnectionType) -> Result<Connection, CircuitError> {
+        match connection_type {
+            Pin0 => Ok(self.out_pos),
+            Pin1 => Ok(self.out_neg),
+            _ => Err(CircuitError::invalid_pin(&self.identifer, connection_type)),
+        }
+    }
+
+    fn current_representative(
+        &self,
+        index: usize,
+        conn_type: ConnectionType,
+        eq: &mut [f64],
+    ) -> Result<(), CircuitError> {
+        match conn_type {
+            Pin0 => eq[index] = -1.0, // current flows out of the node
+            Pin1 => eq[index] = 1.0,  // current flows into the node
+            _ => return Err(CircuitError::invalid_pin(&self.identifer, conn_type)),
+        };
+        Ok(())
+    }
+
+    fn num_eq(&self) -> usize {
+        1
+    }
+
+    fn equation(
+        &self,
+        offset: usize,
+        equation: &mut [f64],
+        eq_id: usize,
+    ) -> Result<f64, CircuitError> {
+        // I = gain I_control
+        // I - gain I_control = 0
+        if eq_id >= self.num_eq() {
+            return Err(CircuitError::invalid_equation(&self.identifer, eq_id));
+        }
+        equation[offset] += 1.0;
+        let (control, sign) = self.locked_control()?;
+        equation[control] -= sign * self.gain;
+        Ok(0.0)
+    }
+
+    fn ac_equation(
+        &self,
+        offset: usize,
+        equation: &mut [Complex<f64>],
+        eq_id: usize,
+        _omega: f64,
+    ) -> Result<Complex<f64>, CircuitError> {
+        // I - gain I_control = 0
+        if eq_id >= self.num_eq() {
+            return Err(CircuitError::invalid_equation(&self.identifer, eq_id));
+        }
+        equation[offset] += 1.0;
+        let (control, sign) = self.locked_control()?;
+        equation[control] -= sign * self.gain;
+        Ok(Complex::from(0.0))
+    }
+
+    fn connection_types(&self) -> &'static [ConnectionType] {
+        &[Pin0, Pin1]
+    }
+
+    fn branches(&self) -> Vec<Branch> {
+        vec![Branch::new(CurrentDefined, Pin0, Pin1)]
+    }
+
+    fn control_component(&self) -> Option<&str> {
+        Some(&self.control)
+    }
+
+    fn control_index(&self) -> Option<usize> {
+        self.control_index
+    }
+
+    fn set_control_index(&mut self, index: usize, sign: f64) {
+        self.control_index = Some(index);
+        self.control_sign = sign;
+    }
+}
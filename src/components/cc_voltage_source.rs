@@ -0,0 +1,174 @@
+use nalgebra::Complex;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    Branch,
+    BranchKind::VoltageDefined,
+    ComponentTrait, Connection,
+    Connection::*,
+    ConnectionType::{self, *},
+    Identifer,
+};
+use crate::error::CircuitError;
+
+/// This struct represents a current controlled voltage source (SPICE H element) in a circuit.
+/// The voltage from Pin0 (out+) to Pin1 (out-) is transresistance times the current of the
+/// component named control, that is the current flowing from its anode through it to its cathode,
+/// or for a voltage source the SPICE current I(V) from its positive node through it.
+/// Its own current flows into out+ and out of out-.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CCVoltageSource {
+    pub identifer: Identifer,
+    pub transresistance: f64,
+    pub control: String,
+    #[serde(skip)]
+    pub control_index: Option<usize>,
+    #[serde(skip)]
+    pub control_sign: f64,
+    pub out_pos: Connection,
+    pub out_neg: Connection,
+}
+
+impl std::fmt::Debug for CCVoltageSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "H ({}) r:{} Control: {} Out+: {:?} Out-: {:?}",
+            self.identifer.id, self.transresistance, self.control, self.out_pos, self.out_neg
+        )
+    }
+}
+
+impl CCVoltageSource {
+    pub fn new(identifer: Identifer, transresistance: f64, control: String) -> Self {
+        Self {
+            identifer,
+            transresistance,
+            control,
+            control_index: None,
+            control_sign: 1.0,
+            out_pos: Disconnected(Pin0),
+            out_neg: Disconnected(Pin1),
+        }
+    }
+
+    // the index and sign of the controlling current are set when the circuit is locked
+    fn locked_control(&self) -> Result<(usize, f64), CircuitError> {
+        let index = self.control_index.ok_or(CircuitError::NotLocked)?;
+        Ok((index, self.control_sign))
+    }
+}
+
+impl ComponentTrait for CCVoltageSource {
+    fn get_id(&self) -> usize {
+        self.identifer.id
+    }
+
+    fn get_name(&self) -> String {
+        self.identifer.name.clone()
+    }
+
+    fn connect(
+        &mut self,
+        node: usize,
+        connection_type: ConnectionType,
+    ) -> Result<(), CircuitError> {
+        match connection_type {
+            Pin0 => self.out_pos = Connected(node, Pin0),
+            Pin1 => self.out_neg = Connected(node, Pin1),
+            _ => return Err(CircuitError::invalid_pin(&self.identifer, connection_type)),
+        };
+        Ok(())
+    }
+
+    fn disconnect(&mut self, connection_type: ConnectionType) -> Result<(), CircuitError> {
+        match connection_type {
+            Pin0 => self.out_pos = Disconnected(Pin0),
+            Pin1 => self.out_neg = Disconnected(Pin1),
+            _ => return Err(CircuitError::invalid_pin(&self.identifer, connection_type)),
+        };
+        Ok(())
+    }
+
+    fn get_connection(&self, connection_type: ConnectionType) -> Result<Connection, CircuitError> {
+        match connection_type {
+            Pin0 => Ok(self.out_pos),
+            Pin1 => Ok(self.out_neg),
+            _ => Err(CircuitError::invalid_pin(&self.identifer, connection_type)),
+        }
+    }
+
+    fn current_representative(
+        &self,
+        index: usize,
+        conn_type: ConnectionType,
+        eq: &mut [f64],
+    ) -> Result<(), CircuitError> {
+        match conn_type {
+            Pin0 => eq[index] = -1.0, // current flows out of the node
+            Pin1 => eq[index] = 1.0,  // current flows into the node
+            _ => return Err(CircuitError::invalid_pin(&self.identifer, conn_type)),
+        };
+        Ok(())
+    }
+
+    fn num_eq(&self) -> usize {
+        1
+    }
+
+    fn equation(&self, _: usize, equation: &mut [f64], eq_id: usize) -> Result<f64, CircuitError> {
+        // V0 - V1 = r I_control
+        // V0 - V1 - r I_control = 0
+        if eq_id >= self.num_eq() {
+            return Err(CircuitError::invalid_equation(&self.identifer, eq_id));
+        }
+        let v0 = self.out_pos.node_id(&self.identifer)?;
+        let v1 = self.out_neg.node_id(&self.identifer)?;
+        equation[v0] += 1.0;
+        equation[v1] -= 1.0;
+        let (control, sign) = self.locked_control()?;
+        equation[control] -= sign * self.transresistance;
+        Ok(0.0)
+    }
+
+    fn ac_equation(
+        &self,
+        _: usize,
+        equation: &mut [Complex<f64>],
+        eq_id: usize,
+        _omega: f64,
+    ) -> Result<Complex<f64>, CircuitError> {
+        // V0 - V1 - r I_control = 0
+        if eq_id >= self.num_eq() {
+            return Err(CircuitError::invalid_equation(&self.identifer, eq_id));
+        }
+        let v0 = self.out_pos.node_id(&self.identifer)?;
+        let v1 = self.out_neg.node_id(&self.identifer)?;
+        equation[v0] += 1.0;
+        equation[v1] -= 1.0;
+        let (control, sign) = self.locked_control()?;
+        equation[control] -= sign * self.transresistance;
+        Ok(Complex::from(0.0))
+    }
+
+    fn connection_types(&self) -> &'static [ConnectionType] {
+        &[Pin0, Pin1]
+    }
+
+    fn branches(&self) -> Vec<Branch> {
+        vec![Branch::new(VoltageDefined, Pin1, Pin0)]
+    }
+
+    fn control_component(&self) -> Option<&str> {
+        Some(&self.control)
+    }
+
+    fn control_index(&self) -> Option<usize> {
+        self.control_index
+    }
+
+    fn set_control_index(&mut self, index: usize, sign: f64) {
+        self.control_index = Some(index);
+        self.control_sign = sign;
+    }
+}
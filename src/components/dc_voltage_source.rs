@@ -1,19 +1,27 @@
+use nalgebra::Complex;
+use serde::{Deserialize, Serialize};
+
 use super::{
+    Branch,
+    BranchKind::VoltageDefined,
     ComponentTrait, Connection,
     Connection::*,
     ConnectionType::{self, *},
     Identifer,
 };
+use crate::error::CircuitError;
 
 /*
 * This struct represents a DC voltage source in a circuit.
 * It has an id, voltage, max_current, and anode and a cathode.
 */
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct DCVoltageSource {
     pub identifer: Identifer,
     pub voltage: f64,
+    pub ac_magnitude: f64,
+    pub ac_phase: f64,
     pub anode: Connection,
     pub cathode: Connection,
 }
@@ -33,13 +41,27 @@ impl DCVoltageSource {
         DCVoltageSource {
             identifer,
             voltage,
+            ac_magnitude: 0.0,
+            ac_phase: 0.0,
             anode: Disconnected(Anode),
             cathode: Disconnected(Cathode),
         }
     }
-}
 
-const PANIC_TEXT: &'static str = "DC VS can only has connection type Anode or Cathode";
+    /// Sets the small signal value used in AC analysis, the phase is given in degrees.
+    pub fn set_ac(&mut self, magnitude: f64, phase: f64) {
+        self.ac_magnitude = magnitude;
+        self.ac_phase = phase;
+    }
+
+    pub fn ac_value(&self) -> Complex<f64> {
+        let phase = self.ac_phase.to_radians();
+        Complex::new(
+            self.ac_magnitude * phase.cos(),
+            self.ac_magnitude * phase.sin(),
+        )
+    }
+}
 
 impl ComponentTrait for DCVoltageSource {
     fn get_id(&self) -> usize {
@@ -50,52 +72,107 @@ impl ComponentTrait for DCVoltageSource {
         self.identifer.name.clone()
     }
 
-    fn connect(&mut self, node_id: usize, connection_type: ConnectionType) {
+    fn connect(
+        &mut self,
+        node_id: usize,
+        connection_type: ConnectionType,
+    ) -> Result<(), CircuitError> {
         match connection_type {
             Anode => self.anode = Connected(node_id, Anode),
             Cathode => self.cathode = Connected(node_id, Cathode),
-            _ => unreachable!("{PANIC_TEXT}"),
+            _ => return Err(CircuitError::invalid_pin(&self.identifer, connection_type)),
         };
+        Ok(())
     }
 
-    fn disconnect(&mut self, connection_type: ConnectionType) {
+    fn disconnect(&mut self, connection_type: ConnectionType) -> Result<(), CircuitError> {
         match connection_type {
             Anode => self.anode = Disconnected(Anode),
             Cathode => self.cathode = Disconnected(Cathode),
-            _ => unreachable!("{PANIC_TEXT}"),
-        }
+            _ => return Err(CircuitError::invalid_pin(&self.identifer, connection_type)),
+        };
+        Ok(())
     }
 
-    fn get_connection(&self, connection_type: ConnectionType) -> Connection {
+    fn get_connection(&self, connection_type: ConnectionType) -> Result<Connection, CircuitError> {
         match connection_type {
-            Anode => self.anode.clone(),
-            Cathode => self.cathode.clone(),
-            _ => unreachable!("{PANIC_TEXT}"),
+            Anode => Ok(self.anode),
+            Cathode => Ok(self.cathode),
+            _ => Err(CircuitError::invalid_pin(&self.identifer, connection_type)),
         }
     }
 
-    fn current_representative(&self, index: usize, conn_type: ConnectionType, eq: &mut [f64]) {
+    fn current_representative(
+        &self,
+        index: usize,
+        conn_type: ConnectionType,
+        eq: &mut [f64],
+    ) -> Result<(), CircuitError> {
         // When a node asks what current the connection provies to the node we
         // return depending on the connection type
         match conn_type {
             Anode => eq[index] = -1.0,  // current flows out of the node
             Cathode => eq[index] = 1.0, // current flows into the node
-            _ => unreachable!("{PANIC_TEXT}"),
-        }
+            _ => return Err(CircuitError::invalid_pin(&self.identifer, conn_type)),
+        };
+        Ok(())
     }
 
     fn num_eq(&self) -> usize {
         1
     }
 
-    fn equation(&self, _: usize, equation: &mut [f64], eq_id: usize) -> f64 {
+    fn equation(&self, _: usize, equation: &mut [f64], eq_id: usize) -> Result<f64, CircuitError> {
         // self.voltage + v1 = v2
         // self.voltage = -v1 + v2
-        assert!(eq_id < self.num_eq());
-        let v1 = self.anode.get_id();
-        let v2 = self.cathode.get_id();
+        if eq_id >= self.num_eq() {
+            return Err(CircuitError::invalid_equation(&self.identifer, eq_id));
+        }
+        let v1 = self.anode.node_id(&self.identifer)?;
+        let v2 = self.cathode.node_id(&self.identifer)?;
         equation[v1] = -1.0;
         equation[v2] = 1.0;
-        self.voltage
+        Ok(self.voltage)
+    }
+
+    fn ac_equation(
+        &self,
+        _: usize,
+        equation: &mut [Complex<f64>],
+        eq_id: usize,
+        _omega: f64,
+    ) -> Result<Complex<f64>, CircuitError> {
+        // only the AC part of the source is present in small signal analysis
+        // -v1 + v2 = V_ac
+        if eq_id >= self.num_eq() {
+            return Err(CircuitError::invalid_equation(&self.identifer, eq_id));
+        }
+        let v1 = self.anode.node_id(&self.identifer)?;
+        let v2 = self.cathode.node_id(&self.identifer)?;
+        equation[v1] = Complex::from(-1.0);
+        equation[v2] = Complex::from(1.0);
+        Ok(self.ac_value())
+    }
+
+    fn connection_types(&self) -> &'static [ConnectionType] {
+        &[Anode, Cathode]
+    }
+
+    fn branches(&self) -> Vec<Branch> {
+        vec![Branch::new(VoltageDefined, Anode, Cathode)]
+    }
+
+    fn sweep_value(&self) -> Result<f64, CircuitError> {
+        Ok(self.voltage)
+    }
+
+    fn set_sweep_value(&mut self, value: f64) -> Result<(), CircuitError> {
+        self.voltage = value;
+        Ok(())
+    }
+
+    // the cathode is the positive node, I(V) flows from it through the source to the anode
+    fn sensed_current_sign(&self) -> f64 {
+        -1.0
     }
 }
@@ -0,0 +1,235 @@
+use nalgebra::Complex;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    Branch,
+    BranchKind::Conductive,
+    ComponentTrait, Connection,
+    Connection::*,
+    ConnectionType::{self, *},
+    Identifer,
+};
+use crate::error::CircuitError;
+
+/// kT/q at 300K
+pub const THERMAL_VOLTAGE: f64 = 0.025852;
+// a small conductance across the junction so a reverse biased diode does not leave a node floating
+const GMIN: f64 = 1e-12;
+
+/// This struct represents a diode in a circuit using the Shockley model
+/// I = Is * (exp(Vd / (n * Vt)) - 1) with a series resistance, current flows from the anode to the cathode.
+/// junction_voltage is the voltage Vd across the junction the diode is linearized around,
+/// it is updated every Newton-Raphson iteration and is not saved, a diode read from JSON starts at 0V.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Diode {
+    pub identifer: Identifer,
+    pub saturation_current: f64,
+    pub emission_coefficient: f64,
+    pub series_resistance: f64,
+    pub anode: Connection,
+    pub cathode: Connection,
+    #[serde(skip)]
+    pub junction_voltage: f64,
+}
+
+impl std::fmt::Debug for Diode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "D ({}) IS:{} N:{} RS:{} Anode: {:?} Cathode: {:?}",
+            self.identifer.id,
+            self.saturation_current,
+            self.emission_coefficient,
+            self.series_resistance,
+            self.anode,
+            self.cathode
+        )
+    }
+}
+
+impl Diode {
+    pub fn new(
+        identifer: Identifer,
+        saturation_current: f64,
+        emission_coefficient: f64,
+        series_resistance: f64,
+    ) -> Self {
+        let mut diode = Self {
+            identifer,
+            saturation_current,
+            emission_coefficient,
+            series_resistance,
+            anode: Disconnected(Anode),
+            cathode: Disconnected(Cathode),
+            junction_voltage: 0.0,
+        };
+        diode.junction_voltage = diode.critical_voltage();
+        diode
+    }
+
+    fn n_vt(&self) -> f64 {
+        self.emission_coefficient * THERMAL_VOLTAGE
+    }
+
+    /// The junction voltage where the current starts to grow fastest, the starting point of the
+    /// Newton-Raphson iteration and above which the voltage steps are limited.
+    pub fn critical_voltage(&self) -> f64 {
+        let n_vt = self.n_vt();
+        n_vt * (n_vt / (std::f64::consts::SQRT_2 * self.saturation_current)).ln()
+    }
+
+    /// The current through the junction and its derivative (the conductance) at the voltage vd.
+    pub fn junction_current(&self, vd: f64) -> (f64, f64) {
+        let n_vt = self.n_vt();
+        let exp = (vd / n_vt).exp();
+        let current = self.saturation_current * (exp - 1.0) + GMIN * vd;
+        let conductance = self.saturation_current / n_vt * exp + GMIN;
+        (current, conductance)
+    }
+
+    /// Moves the linearization point towards the junction voltage of the last solution.
+    /// Large steps above the critical voltage are limited logarithmically so the exponential
+    /// does not overflow, returns true if the step was limited.
+    pub fn update_junction_voltage(&mut self, vd: f64) -> bool {
+        let (limited, vd) = self.limit_junction_voltage(vd);
+        self.junction_voltage = vd;
+        limited
+    }
+
+    fn limit_junction_voltage(&self, vd: f64) -> (bool, f64) {
+        let n_vt = self.n_vt();
+        let old = self.junction_voltage;
+        if vd <= self.critical_voltage() || (vd - old).abs() <= 2.0 * n_vt {
+            return (false, vd);
+        }
+        if old > 0.0 {
+            let arg = 1.0 + (vd - old) / n_vt;
+            if arg > 0.0 {
+                (true, old + n_vt * arg.ln())
+            } else {
+                (true, self.critical_voltage())
+            }
+        } else {
+            (true, n_vt * (vd / n_vt).ln())
+        }
+    }
+}
+
+impl ComponentTrait for Diode {
+    fn get_id(&self) -> usize {
+        self.identifer.id
+    }
+
+    fn get_name(&self) -> String {
+        self.identifer.name.clone()
+    }
+
+    fn connect(
+        &mut self,
+        node: usize,
+        connection_type: ConnectionType,
+    ) -> Result<(), CircuitError> {
+        match connection_type {
+            Anode => self.anode = Connected(node, Anode),
+            Cathode => self.cathode = Connected(node, Cathode),
+            _ => return Err(CircuitError::invalid_pin(&self.identifer, connection_type)),
+        };
+        Ok(())
+    }
+
+    fn disconnect(&mut self, connection_type: ConnectionType) -> Result<(), CircuitError> {
+        match connection_type {
+            Anode => self.anode = Disconnected(Anode),
+            Cathode => self.cathode = Disconnected(Cathode),
+            _ => return Err(CircuitError::invalid_pin(&self.identifer, connection_type)),
+        };
+        Ok(())
+    }
+
+    fn get_connection(&self, connection_type: ConnectionType) -> Result<Connection, CircuitError> {
+        match connection_type {
+            Anode => Ok(self.anode),
+            Cathode => Ok(self.cathode),
+            _ => Err(CircuitError::invalid_pin(&self.identifer, connection_type)),
+        }
+    }
+
+    fn current_representative(
+        &self,
+        index: usize,
+        conn_type: ConnectionType,
+        eq: &mut [f64],
+    ) -> Result<(), CircuitError> {
+        match conn_type {
+            Anode => eq[index] = -1.0,  // current flows out of the node
+            Cathode => eq[index] = 1.0, // current flows into the node
+            _ => return Err(CircuitError::invalid_pin(&self.identifer, conn_type)),
+        };
+        Ok(())
+    }
+
+    fn num_eq(&self) -> usize {
+        1
+    }
+
+    fn equation(
+        &self,
+        offset: usize,
+        equation: &mut [f64],
+        eq_id: usize,
+    ) -> Result<f64, CircuitError> {
+        // linearized around Vd0: I = Id0 + gd (Vd - Vd0) with Vd = V1 - V2 - Rs I
+        // gd V1 - gd V2 - (1 + gd Rs) I = gd Vd0 - Id0
+        if eq_id >= self.num_eq() {
+            return Err(CircuitError::invalid_equation(&self.identifer, eq_id));
+        }
+        let v1 = self.anode.node_id(&self.identifer)?;
+        let v2 = self.cathode.node_id(&self.identifer)?;
+        let vd = self.junction_voltage;
+        let (current, gd) = self.junction_current(vd);
+        equation[v1] += gd;
+        equation[v2] -= gd;
+        equation[offset] -= 1.0 + gd * self.series_resistance;
+        Ok(gd * vd - current)
+    }
+
+    fn ac_equation(
+        &self,
+        offset: usize,
+        equation: &mut [Complex<f64>],
+        eq_id: usize,
+        _omega: f64,
+    ) -> Result<Complex<f64>, CircuitError> {
+        // the small signal conductance at the operating point
+        // gd V1 - gd V2 - (1 + gd Rs) I = 0
+        if eq_id >= self.num_eq() {
+            return Err(CircuitError::invalid_equation(&self.identifer, eq_id));
+        }
+        let v1 = self.anode.node_id(&self.identifer)?;
+        let v2 = self.cathode.node_id(&self.identifer)?;
+        let (_, gd) = self.junction_current(self.junction_voltage);
+        equation[v1] += gd;
+        equation[v2] -= gd;
+        equation[offset] -= 1.0 + gd * self.series_resistance;
+        Ok(Complex::from(0.0))
+    }
+
+    fn connection_types(&self) -> &'static [ConnectionType] {
+        &[Anode, Cathode]
+    }
+
+    fn branches(&self) -> Vec<Branch> {
+        vec![Branch::new(Conductive, Anode, Cathode)]
+    }
+
+    fn is_nonlinear(&self) -> bool {
+        true
+    }
+
+    fn update_operating_point(&mut self, x: &[f64], offset: usize) -> Result<bool, CircuitError> {
+        let v1 = self.anode.node_id(&self.identifer)?;
+        let v2 = self.cathode.node_id(&self.identifer)?;
+        let vd = x[v1] - x[v2] - self.series_resistance * x[offset];
+        Ok(self.update_junction_voltage(vd))
+    }
+}
@@ -1,13 +1,17 @@
+use nalgebra::Complex;
+use serde::{Deserialize, Serialize};
+
 use super::{
+    Branch,
+    BranchKind::VoltageDefined,
     ComponentTrait, Connection,
     Connection::*,
     ConnectionType::{self, *},
     Identifer,
 };
-/*
-* This struct is a ground node in a circuit it has a potential and has a single NodeConnection.
-*/
-#[derive(Clone)]
+use crate::error::CircuitError;
+/// This struct is a ground node in a circuit it has a potential and has a single NodeConnection.
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Ground {
     pub identifer: Identifer,
     pub node: Connection,
@@ -28,8 +32,6 @@ impl Ground {
     }
 }
 
-const PANIC_TEXT: &'static str = "Ground can only has connection type GroundConnection";
-
 impl ComponentTrait for Ground {
     fn get_id(&self) -> usize {
         self.identifer.id
@@ -39,45 +41,83 @@ impl ComponentTrait for Ground {
         self.identifer.name.clone()
     }
 
-    fn connect(&mut self, node_id: usize, connection_type: ConnectionType) {
+    fn connect(
+        &mut self,
+        node_id: usize,
+        connection_type: ConnectionType,
+    ) -> Result<(), CircuitError> {
         match connection_type {
             GroundConnection => self.node = Connected(node_id, GroundConnection),
-            _ => unreachable!("{PANIC_TEXT}"),
+            _ => return Err(CircuitError::invalid_pin(&self.identifer, connection_type)),
         };
+        Ok(())
     }
 
-    fn disconnect(&mut self, connection_type: ConnectionType) {
+    fn disconnect(&mut self, connection_type: ConnectionType) -> Result<(), CircuitError> {
         match connection_type {
             GroundConnection => self.node = Disconnected(GroundConnection),
-            _ => unreachable!("{PANIC_TEXT}"),
+            _ => return Err(CircuitError::invalid_pin(&self.identifer, connection_type)),
         };
+        Ok(())
     }
 
-    fn get_connection(&self, connection_type: ConnectionType) -> Connection {
+    fn get_connection(&self, connection_type: ConnectionType) -> Result<Connection, CircuitError> {
         match connection_type {
-            GroundConnection => self.node.clone(),
-            _ => unreachable!("{PANIC_TEXT}"),
+            GroundConnection => Ok(self.node),
+            _ => Err(CircuitError::invalid_pin(&self.identifer, connection_type)),
         }
     }
 
-    fn current_representative(&self, index: usize, conn_type: ConnectionType, eq: &mut [f64]) {
+    fn current_representative(
+        &self,
+        index: usize,
+        conn_type: ConnectionType,
+        eq: &mut [f64],
+    ) -> Result<(), CircuitError> {
         // When a node asks what current the connection provies to the node we
         // return depending on the connection type
         match conn_type {
             GroundConnection => eq[index] = -1.0, // current flows out of the node
-            _ => unreachable!("{PANIC_TEXT}"),
-        }
+            _ => return Err(CircuitError::invalid_pin(&self.identifer, conn_type)),
+        };
+        Ok(())
     }
 
     fn num_eq(&self) -> usize {
         1
     }
 
-    fn equation(&self, _: usize, equation: &mut [f64], eq_id: usize) -> f64 {
+    fn equation(&self, _: usize, equation: &mut [f64], eq_id: usize) -> Result<f64, CircuitError> {
         // V = 0
-        assert!(eq_id < self.num_eq());
-        let v = self.node.get_id();
+        if eq_id >= self.num_eq() {
+            return Err(CircuitError::invalid_equation(&self.identifer, eq_id));
+        }
+        let v = self.node.node_id(&self.identifer)?;
         equation[v] = 1.0;
-        0.0
+        Ok(0.0)
+    }
+
+    fn ac_equation(
+        &self,
+        _: usize,
+        equation: &mut [Complex<f64>],
+        eq_id: usize,
+        _omega: f64,
+    ) -> Result<Complex<f64>, CircuitError> {
+        // V = 0
+        if eq_id >= self.num_eq() {
+            return Err(CircuitError::invalid_equation(&self.identifer, eq_id));
+        }
+        let v = self.node.node_id(&self.identifer)?;
+        equation[v] = Complex::from(1.0);
+        Ok(Complex::from(0.0))
+    }
+
+    fn connection_types(&self) -> &'static [ConnectionType] {
+        &[GroundConnection]
+    }
+
+    fn branches(&self) -> Vec<Branch> {
+        vec![Branch::to_reference(VoltageDefined, GroundConnection)]
     }
 }
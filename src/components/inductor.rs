@@ -0,0 +1,228 @@
+use nalgebra::Complex;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    Branch,
+    BranchKind::VoltageDefined,
+    ComponentTrait, Connection,
+    Connection::*,
+    ConnectionType::{self, *},
+    Identifer, IntegrationMethod, ReactiveMode,
+};
+use crate::error::CircuitError;
+
+/// This struct represents an inductor in a circuit
+/// It has an identifer, inductance, an optional initial current and two nodes.
+/// voltage and current hold the state of the last accepted time step during transient analysis,
+/// like the mode they are solver state and are not saved.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Inductor {
+    pub identifer: Identifer,
+    pub inductance: f64,
+    pub initial_current: Option<f64>,
+    pub node1: Connection,
+    pub node2: Connection,
+    #[serde(skip)]
+    pub mode: ReactiveMode,
+    #[serde(skip)]
+    pub voltage: f64,
+    #[serde(skip)]
+    pub current: f64,
+}
+
+impl std::fmt::Debug for Inductor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "L ({}) L:{} IC:{:?} Node1: {:?} Node2: {:?}",
+            self.identifer.id, self.inductance, self.initial_current, self.node1, self.node2
+        )
+    }
+}
+
+impl Inductor {
+    pub fn new(identifer: Identifer, inductance: f64) -> Self {
+        Self {
+            identifer,
+            inductance,
+            initial_current: None,
+            node1: Disconnected(Anode),
+            node2: Disconnected(Cathode),
+            mode: ReactiveMode::OperatingPoint,
+            voltage: 0.0,
+            current: 0.0,
+        }
+    }
+
+    pub fn set_initial_current(&mut self, current: f64) {
+        self.initial_current = Some(current);
+    }
+
+    pub fn set_mode(&mut self, mode: ReactiveMode) {
+        self.mode = mode;
+    }
+
+    /// The impedance of the inductor at the angular frequency omega, Z = jwL.
+    pub fn impedance(&self, omega: f64) -> Complex<f64> {
+        Complex::new(0.0, omega * self.inductance)
+    }
+
+    /// Prepares the companion model for a time step of length step using the stored state.
+    /// Backward euler:  v = L/h * (i - i_prev)
+    /// Trapezoidal:     v = 2L/h * (i - i_prev) - v_prev
+    pub fn prepare_companion(&mut self, step: f64, method: IntegrationMethod) {
+        let (resistance, history) = match method {
+            IntegrationMethod::BackwardEuler => {
+                let r = self.inductance / step;
+                (r, r * self.current)
+            }
+            IntegrationMethod::Trapezoidal => {
+                let r = 2.0 * self.inductance / step;
+                (r, r * self.current + self.voltage)
+            }
+        };
+        self.mode = ReactiveMode::Companion(resistance, history);
+    }
+
+    /// Stores the voltage and current of an accepted time point as the state of the next step.
+    pub fn accept(&mut self, voltage: f64, current: f64) {
+        self.voltage = voltage;
+        self.current = current;
+    }
+}
+
+impl ComponentTrait for Inductor {
+    fn get_id(&self) -> usize {
+        self.identifer.id
+    }
+
+    fn get_name(&self) -> String {
+        self.identifer.name.clone()
+    }
+
+    fn connect(
+        &mut self,
+        node: usize,
+        connection_type: ConnectionType,
+    ) -> Result<(), CircuitError> {
+        match connection_type {
+            Anode => self.node1 = Connected(node, Anode),
+            Cathode => self.node2 = Connected(node, Cathode),
+            _ => return Err(CircuitError::invalid_pin(&self.identifer, connection_type)),
+        };
+        Ok(())
+    }
+
+    fn disconnect(&mut self, connection_type: ConnectionType) -> Result<(), CircuitError> {
+        match connection_type {
+            Anode => self.node1 = Disconnected(Anode),
+            Cathode => self.node2 = Disconnected(Cathode),
+            _ => return Err(CircuitError::invalid_pin(&self.identifer, connection_type)),
+        };
+        Ok(())
+    }
+
+    fn get_connection(&self, connection_type: ConnectionType) -> Result<Connection, CircuitError> {
+        match connection_type {
+            Anode => Ok(self.node1),
+            Cathode => Ok(self.node2),
+            _ => Err(CircuitError::invalid_pin(&self.identifer, connection_type)),
+        }
+    }
+
+    fn current_representative(
+        &self,
+        index: usize,
+        conn_type: ConnectionType,
+        eq: &mut [f64],
+    ) -> Result<(), CircuitError> {
+        match conn_type {
+            Anode => eq[index] = -1.0,  // current flows out of the node
+            Cathode => eq[index] = 1.0, // current flows into the node
+            _ => return Err(CircuitError::invalid_pin(&self.identifer, conn_type)),
+        };
+        Ok(())
+    }
+
+    fn num_eq(&self) -> usize {
+        1
+    }
+
+    fn equation(
+        &self,
+        offset: usize,
+        equation: &mut [f64],
+        eq_id: usize,
+    ) -> Result<f64, CircuitError> {
+        if eq_id >= self.num_eq() {
+            return Err(CircuitError::invalid_equation(&self.identifer, eq_id));
+        }
+        let v1 = self.node1.node_id(&self.identifer)?;
+        let v2 = self.node2.node_id(&self.identifer)?;
+        match (self.mode, self.initial_current) {
+            (ReactiveMode::Companion(r, history), _) => {
+                // V1 - V2 = R I - V_hist
+                // V1 - V2 - R I = -V_hist
+                equation[v1] = 1.0;
+                equation[v2] = -1.0;
+                equation[offset] = -r;
+                Ok(-history)
+            }
+            (ReactiveMode::InitialCondition, Some(current)) => {
+                // I = I_ic
+                equation[offset] = 1.0;
+                Ok(current)
+            }
+            _ => {
+                // short circuit, V1 - V2 = 0
+                equation[v1] = 1.0;
+                equation[v2] = -1.0;
+                Ok(0.0)
+            }
+        }
+    }
+
+    fn ac_equation(
+        &self,
+        offset: usize,
+        equation: &mut [Complex<f64>],
+        eq_id: usize,
+        omega: f64,
+    ) -> Result<Complex<f64>, CircuitError> {
+        // V1 - V2 = jwL I
+        // V1 - V2 - jwL I = 0
+        if eq_id >= self.num_eq() {
+            return Err(CircuitError::invalid_equation(&self.identifer, eq_id));
+        }
+        let v1 = self.node1.node_id(&self.identifer)?;
+        let v2 = self.node2.node_id(&self.identifer)?;
+        equation[v1] = Complex::from(1.0);
+        equation[v2] = Complex::from(-1.0);
+        equation[offset] = -self.impedance(omega);
+        Ok(Complex::from(0.0))
+    }
+
+    fn connection_types(&self) -> &'static [ConnectionType] {
+        &[Anode, Cathode]
+    }
+
+    fn branches(&self) -> Vec<Branch> {
+        // an inductor is shorted in DC
+        vec![Branch::new(VoltageDefined, Anode, Cathode)]
+    }
+
+    fn set_reactive_mode(&mut self, mode: ReactiveMode) {
+        self.set_mode(mode);
+    }
+
+    fn begin_step(&mut self, step: f64, method: IntegrationMethod) {
+        self.prepare_companion(step, method);
+    }
+
+    fn accept_step(&mut self, x: &[f64], offset: usize) -> Result<(), CircuitError> {
+        let v1 = self.node1.node_id(&self.identifer)?;
+        let v2 = self.node2.node_id(&self.identifer)?;
+        self.accept(x[v1] - x[v2], x[offset]);
+        Ok(())
+    }
+}
@@ -1,10 +1,12 @@
+use serde::{Deserialize, Serialize};
+
 use super::{Connection, ConnectionType, Identifer};
 /*
 * This struct represents a node in a circuit. NOTE: a not is NOT a component.
 * It contains the nodes id, potential, connections to and from the node, it can be locked or unlocked.
 */
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Node {
     pub identifer: Identifer,
     pub potential: f64,
@@ -48,8 +50,12 @@ impl Node {
         self.locked = true;
     }
 
+    /// Adds a pin to the node, a pin that is already connected to it is not added again.
     pub fn add_connection(&mut self, comp_id: usize, con_type: ConnectionType) {
-        let connection = Connection::Connected(comp_id, con_type);
-        self.connections.push(connection);
+        let connected = |c: &Connection| matches!(*c, Connection::Connected(id, pin) if id == comp_id && pin == con_type);
+        if !self.connections.iter().any(connected) {
+            self.connections
+                .push(Connection::Connected(comp_id, con_type));
+        }
     }
 }
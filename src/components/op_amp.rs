@@ -0,0 +1,291 @@
+use nalgebra::Complex;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::{
+    Branch,
+    BranchKind::VoltageDefined,
+    ComponentTrait, Connection,
+    Connection::*,
+    ConnectionType::{self, *},
+    Identifer,
+};
+use crate::error::CircuitError;
+
+// the margin past a rail relative to the rail voltage, at least 1V, before the region changes
+const REGION_MARGIN: f64 = 1e-9;
+
+/// How the output of an operational amplifier follows its inputs.
+/// Ideal has infinite gain so the inputs are held at the same potential (a virtual short).
+/// Finite has output = gain * (V+ - V-) clamped between the rails v_min and v_max.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum OpAmpModel {
+    Ideal,
+    Finite {
+        gain: f64,
+        #[serde(
+            serialize_with = "serialize_rail",
+            deserialize_with = "deserialize_v_min"
+        )]
+        v_min: f64,
+        #[serde(
+            serialize_with = "serialize_rail",
+            deserialize_with = "deserialize_v_max"
+        )]
+        v_max: f64,
+    },
+}
+
+// JSON has no infinity, a missing rail is stored as null
+fn serialize_rail<S: Serializer>(rail: &f64, serializer: S) -> Result<S::Ok, S::Error> {
+    rail.is_finite().then_some(*rail).serialize(serializer)
+}
+
+fn deserialize_v_min<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+    Ok(Option::deserialize(deserializer)?.unwrap_or(f64::NEG_INFINITY))
+}
+
+fn deserialize_v_max<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+    Ok(Option::deserialize(deserializer)?.unwrap_or(f64::INFINITY))
+}
+
+/// The part of the transfer curve a finite gain operational amplifier is operating in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OpAmpRegion {
+    #[default]
+    Linear,
+    SaturatedLow,
+    SaturatedHigh,
+}
+
+/// This struct represents an operational amplifier in a circuit.
+/// It has a non inverting input, an inverting input that draw no current, and an output
+/// whose potential is set relative to ground, the output current is its own unknown.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct OpAmp {
+    pub identifer: Identifer,
+    pub model: OpAmpModel,
+    #[serde(skip)]
+    pub region: OpAmpRegion,
+    pub non_inverting: Connection,
+    pub inverting: Connection,
+    pub output: Connection,
+}
+
+impl std::fmt::Debug for OpAmp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "OpAmp ({}) {:?} In+: {:?} In-: {:?} Out: {:?}",
+            self.identifer.id, self.model, self.non_inverting, self.inverting, self.output
+        )
+    }
+}
+
+impl OpAmp {
+    pub fn new(identifer: Identifer) -> Self {
+        Self::with_model(identifer, OpAmpModel::Ideal)
+    }
+
+    /// A finite gain operational amplifier whose output is clamped between v_min and v_max,
+    /// use infinite rails for an unclamped output.
+    pub fn finite(identifer: Identifer, gain: f64, v_min: f64, v_max: f64) -> Self {
+        Self::with_model(identifer, OpAmpModel::Finite { gain, v_min, v_max })
+    }
+
+    fn with_model(identifer: Identifer, model: OpAmpModel) -> Self {
+        Self {
+            identifer,
+            model,
+            region: OpAmpRegion::Linear,
+            non_inverting: Disconnected(NonInverting),
+            inverting: Disconnected(Inverting),
+            output: Disconnected(Output),
+        }
+    }
+
+    /// Only a finite gain operational amplifier with rails has to be solved iteratively.
+    pub fn is_clamped(&self) -> bool {
+        match self.model {
+            OpAmpModel::Ideal => false,
+            OpAmpModel::Finite { v_min, v_max, .. } => v_min.is_finite() || v_max.is_finite(),
+        }
+    }
+
+    /// Picks the region of the transfer curve for the input voltage of the last solution,
+    /// returns true if the region changed.
+    /// The region only changes once the output is past a rail by a margin, an operating point
+    /// right at a rail would otherwise flip between two regions on rounding errors.
+    pub fn update_region(&mut self, input_voltage: f64) -> bool {
+        let OpAmpModel::Finite { gain, v_min, v_max } = self.model else {
+            return false;
+        };
+        let output = gain * input_voltage;
+        let above = output > v_max + rail_margin(v_max);
+        let below = output < v_min - rail_margin(v_min);
+        let region = match self.region {
+            _ if above => OpAmpRegion::SaturatedHigh,
+            _ if below => OpAmpRegion::SaturatedLow,
+            OpAmpRegion::SaturatedHigh if output >= v_max - rail_margin(v_max) => {
+                OpAmpRegion::SaturatedHigh
+            }
+            OpAmpRegion::SaturatedLow if output <= v_min + rail_margin(v_min) => {
+                OpAmpRegion::SaturatedLow
+            }
+            _ => OpAmpRegion::Linear,
+        };
+        let changed = region != self.region;
+        self.region = region;
+        changed
+    }
+}
+
+// how far the output has to move past a rail before the region changes
+fn rail_margin(rail: f64) -> f64 {
+    if rail.is_finite() {
+        REGION_MARGIN * rail.abs().max(1.0)
+    } else {
+        0.0
+    }
+}
+
+impl ComponentTrait for OpAmp {
+    fn get_id(&self) -> usize {
+        self.identifer.id
+    }
+
+    fn get_name(&self) -> String {
+        self.identifer.name.clone()
+    }
+
+    fn connect(
+        &mut self,
+        node: usize,
+        connection_type: ConnectionType,
+    ) -> Result<(), CircuitError> {
+        match connection_type {
+            NonInverting => self.non_inverting = Connected(node, NonInverting),
+            Inverting => self.inverting = Connected(node, Inverting),
+            Output => self.output = Connected(node, Output),
+            _ => return Err(CircuitError::invalid_pin(&self.identifer, connection_type)),
+        };
+        Ok(())
+    }
+
+    fn disconnect(&mut self, connection_type: ConnectionType) -> Result<(), CircuitError> {
+        match connection_type {
+            NonInverting => self.non_inverting = Disconnected(NonInverting),
+            Inverting => self.inverting = Disconnected(Inverting),
+            Output => self.output = Disconnected(Output),
+            _ => return Err(CircuitError::invalid_pin(&self.identifer, connection_type)),
+        };
+        Ok(())
+    }
+
+    fn get_connection(&self, connection_type: ConnectionType) -> Result<Connection, CircuitError> {
+        match connection_type {
+            NonInverting => Ok(self.non_inverting),
+            Inverting => Ok(self.inverting),
+            Output => Ok(self.output),
+            _ => Err(CircuitError::invalid_pin(&self.identifer, connection_type)),
+        }
+    }
+
+    fn current_representative(
+        &self,
+        index: usize,
+        conn_type: ConnectionType,
+        eq: &mut [f64],
+    ) -> Result<(), CircuitError> {
+        match conn_type {
+            Output => eq[index] = 1.0,     // current flows into the node
+            NonInverting | Inverting => {} // the inputs do not draw any current
+            _ => return Err(CircuitError::invalid_pin(&self.identifer, conn_type)),
+        };
+        Ok(())
+    }
+
+    fn num_eq(&self) -> usize {
+        1
+    }
+
+    fn equation(&self, _: usize, equation: &mut [f64], eq_id: usize) -> Result<f64, CircuitError> {
+        if eq_id >= self.num_eq() {
+            return Err(CircuitError::invalid_equation(&self.identifer, eq_id));
+        }
+        let v_pos = self.non_inverting.node_id(&self.identifer)?;
+        let v_neg = self.inverting.node_id(&self.identifer)?;
+        let v_out = self.output.node_id(&self.identifer)?;
+        match (self.model, self.region) {
+            (OpAmpModel::Ideal, _) => {
+                // V+ - V- = 0
+                equation[v_pos] += 1.0;
+                equation[v_neg] -= 1.0;
+                Ok(0.0)
+            }
+            (OpAmpModel::Finite { gain, .. }, OpAmpRegion::Linear) => {
+                // Vout = A (V+ - V-)
+                // Vout - A V+ + A V- = 0
+                equation[v_out] += 1.0;
+                equation[v_pos] -= gain;
+                equation[v_neg] += gain;
+                Ok(0.0)
+            }
+            (OpAmpModel::Finite { v_min, .. }, OpAmpRegion::SaturatedLow) => {
+                equation[v_out] = 1.0;
+                Ok(v_min)
+            }
+            (OpAmpModel::Finite { v_max, .. }, OpAmpRegion::SaturatedHigh) => {
+                equation[v_out] = 1.0;
+                Ok(v_max)
+            }
+        }
+    }
+
+    fn ac_equation(
+        &self,
+        _: usize,
+        equation: &mut [Complex<f64>],
+        eq_id: usize,
+        _omega: f64,
+    ) -> Result<Complex<f64>, CircuitError> {
+        // the same as in DC, a saturated output does not respond to small signals
+        if eq_id >= self.num_eq() {
+            return Err(CircuitError::invalid_equation(&self.identifer, eq_id));
+        }
+        let v_pos = self.non_inverting.node_id(&self.identifer)?;
+        let v_neg = self.inverting.node_id(&self.identifer)?;
+        let v_out = self.output.node_id(&self.identifer)?;
+        match (self.model, self.region) {
+            (OpAmpModel::Ideal, _) => {
+                equation[v_pos] += 1.0;
+                equation[v_neg] -= 1.0;
+            }
+            (OpAmpModel::Finite { gain, .. }, OpAmpRegion::Linear) => {
+                equation[v_out] += 1.0;
+                equation[v_pos] -= gain;
+                equation[v_neg] += gain;
+            }
+            (OpAmpModel::Finite { .. }, _) => equation[v_out] = Complex::from(1.0),
+        }
+        Ok(Complex::from(0.0))
+    }
+
+    fn connection_types(&self) -> &'static [ConnectionType] {
+        &[NonInverting, Inverting, Output]
+    }
+
+    fn branches(&self) -> Vec<Branch> {
+        // the output is driven relative to ground, the inputs only sense a voltage
+        vec![Branch::to_reference(VoltageDefined, Output)]
+    }
+
+    fn is_nonlinear(&self) -> bool {
+        self.is_clamped()
+    }
+
+    fn update_operating_point(&mut self, x: &[f64], _offset: usize) -> Result<bool, CircuitError> {
+        let v_pos = self.non_inverting.node_id(&self.identifer)?;
+        let v_neg = self.inverting.node_id(&self.identifer)?;
+        Ok(self.update_region(x[v_pos] - x[v_neg]))
+    }
+}
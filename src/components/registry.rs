@@ -0,0 +1,105 @@
+use std::collections::HashMap;
+use std::sync::{OnceLock, RwLock};
+
+use super::{ComponentTrait, Identifer};
+use crate::error::CircuitError;
+
+/// Creates a component of a registered type from its identifer and named parameters.
+pub type ComponentFactory =
+    fn(Identifer, &[(String, f64)]) -> Result<Box<dyn ComponentTrait>, CircuitError>;
+
+fn registry() -> &'static RwLock<HashMap<String, ComponentFactory>> {
+    static REGISTRY: OnceLock<RwLock<HashMap<String, ComponentFactory>>> = OnceLock::new();
+    REGISTRY.get_or_init(|| RwLock::new(HashMap::new()))
+}
+
+/// Makes a component type defined outside of this crate available to netlists and JSON documents
+/// under type_name, a name can only be registered once.
+pub fn register_component(type_name: &str, factory: ComponentFactory) -> Result<(), CircuitError> {
+    let mut registry = registry().write().unwrap_or_else(|e| e.into_inner());
+    if registry.contains_key(type_name) {
+        return Err(CircuitError::InvalidParameter(format!(
+            "a component type named {type_name} is already registered"
+        )));
+    }
+    registry.insert(type_name.to_string(), factory);
+    Ok(())
+}
+
+pub fn create_component(
+    type_name: &str,
+    identifer: Identifer,
+    parameters: &[(String, f64)],
+) -> Result<Box<dyn ComponentTrait>, CircuitError> {
+    let factory = registry()
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(type_name)
+        .copied()
+        .ok_or_else(|| {
+            CircuitError::InvalidParameter(format!("there is no component type named {type_name}"))
+        })?;
+    factory(identifer, parameters)
+}
+
+/*
+* Saves a custom component as its registered type, parameters and connections,
+* it is created again through the registry when it is read.
+*/
+pub(super) mod custom_component {
+    use serde::de::Error as _;
+    use serde::ser::Error as _;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::super::{ComponentTrait, Connection, Identifer};
+
+    #[derive(Serialize, Deserialize)]
+    struct Saved {
+        registered_type: String,
+        id: usize,
+        name: String,
+        parameters: Vec<(String, f64)>,
+        connections: Vec<Connection>,
+    }
+
+    #[allow(clippy::borrowed_box)]
+    pub fn serialize<S: Serializer>(
+        component: &Box<dyn ComponentTrait>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let registered_type = component.registered_type().ok_or_else(|| {
+            S::Error::custom(format!("{} is not a registered type", component.get_name()))
+        })?;
+        let connections = component
+            .connection_types()
+            .iter()
+            .map(|pin| component.get_connection(*pin))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(S::Error::custom)?;
+        Saved {
+            registered_type: registered_type.to_string(),
+            id: component.get_id(),
+            name: component.get_name(),
+            parameters: component.parameters(),
+            connections,
+        }
+        .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Box<dyn ComponentTrait>, D::Error> {
+        let saved = Saved::deserialize(deserializer)?;
+        let identifer =
+            Identifer::new(saved.name.clone(), saved.id, saved.name.clone(), saved.name);
+        let mut component =
+            super::create_component(&saved.registered_type, identifer, &saved.parameters)
+                .map_err(D::Error::custom)?;
+        for connection in saved.connections {
+            if let Connection::Connected(node, pin) = connection {
+                component.connect(node, pin).map_err(D::Error::custom)?;
+            }
+        }
+        Ok(component)
+    }
+}
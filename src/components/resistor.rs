@@ -1,15 +1,19 @@
+use nalgebra::Complex;
+use serde::{Deserialize, Serialize};
+
 use super::{
+    Branch,
+    BranchKind::Conductive,
     ComponentTrait, Connection,
     Connection::*,
     ConnectionType::{self, *},
     Identifer,
 };
+use crate::error::CircuitError;
 
-/*
-* This struct represents a resistor in a circuit
-* It has an identifer, resistance, and two nodes.
-*/
-#[derive(Clone)]
+/// This struct represents a resistor in a circuit
+/// It has an identifer, resistance, and two nodes.
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Resistor {
     pub identifer: Identifer,
     pub resistance: f64,
@@ -38,8 +42,6 @@ impl Resistor {
     }
 }
 
-const PANIC_TEXT: &'static str = "Resistor can only has connection type Anode or Cathode";
-
 impl ComponentTrait for Resistor {
     fn get_id(&self) -> usize {
         self.identifer.id
@@ -49,54 +51,109 @@ impl ComponentTrait for Resistor {
         self.identifer.name.clone()
     }
 
-    fn connect(&mut self, node: usize, connection_type: ConnectionType) {
+    fn connect(
+        &mut self,
+        node: usize,
+        connection_type: ConnectionType,
+    ) -> Result<(), CircuitError> {
         match connection_type {
             Anode => self.node1 = Connected(node, Anode),
             Cathode => self.node2 = Connected(node, Cathode),
-            _ => unreachable!("{PANIC_TEXT}"),
+            _ => return Err(CircuitError::invalid_pin(&self.identifer, connection_type)),
         };
+        Ok(())
     }
 
-    fn disconnect(&mut self, connection_type: ConnectionType) {
+    fn disconnect(&mut self, connection_type: ConnectionType) -> Result<(), CircuitError> {
         match connection_type {
             Anode => self.node1 = Disconnected(Anode),
             Cathode => self.node2 = Disconnected(Cathode),
-            _ => unreachable!("{PANIC_TEXT}"),
+            _ => return Err(CircuitError::invalid_pin(&self.identifer, connection_type)),
         };
+        Ok(())
     }
 
-    fn get_connection(&self, connection_type: ConnectionType) -> Connection {
+    fn get_connection(&self, connection_type: ConnectionType) -> Result<Connection, CircuitError> {
         match connection_type {
-            Anode => self.node1.clone(),
-            Cathode => self.node2.clone(),
-            _ => unreachable!("{PANIC_TEXT}"),
+            Anode => Ok(self.node1),
+            Cathode => Ok(self.node2),
+            _ => Err(CircuitError::invalid_pin(&self.identifer, connection_type)),
         }
     }
 
-    fn current_representative(&self, index: usize, conn_type: ConnectionType, eq: &mut [f64]) {
+    fn current_representative(
+        &self,
+        index: usize,
+        conn_type: ConnectionType,
+        eq: &mut [f64],
+    ) -> Result<(), CircuitError> {
         match conn_type {
             Anode => eq[index] = -1.0,  // current flows out of the node
             Cathode => eq[index] = 1.0, // current flows into the node
-            _ => unreachable!("{PANIC_TEXT}"),
-        }
+            _ => return Err(CircuitError::invalid_pin(&self.identifer, conn_type)),
+        };
+        Ok(())
     }
 
     fn num_eq(&self) -> usize {
         1
     }
 
-    fn equation(&self, offset: usize, equation: &mut [f64], eq_id: usize) -> f64 {
+    fn equation(
+        &self,
+        offset: usize,
+        equation: &mut [f64],
+        eq_id: usize,
+    ) -> Result<f64, CircuitError> {
         // V = IR
         // V = V1 - V2 = IR
         // V1 - V2 = IR
         // V1 - V2 - IR = 0
-        assert!(eq_id < self.num_eq());
-        let v1 = self.node1.get_id();
-        let v2 = self.node2.get_id();
+        if eq_id >= self.num_eq() {
+            return Err(CircuitError::invalid_equation(&self.identifer, eq_id));
+        }
+        let v1 = self.node1.node_id(&self.identifer)?;
+        let v2 = self.node2.node_id(&self.identifer)?;
         let r = self.resistance;
         equation[v1] = 1.0;
         equation[v2] = -1.0;
         equation[offset] = -r;
-        0.0
+        Ok(0.0)
+    }
+
+    fn ac_equation(
+        &self,
+        offset: usize,
+        equation: &mut [Complex<f64>],
+        eq_id: usize,
+        _omega: f64,
+    ) -> Result<Complex<f64>, CircuitError> {
+        // V1 - V2 - IR = 0
+        if eq_id >= self.num_eq() {
+            return Err(CircuitError::invalid_equation(&self.identifer, eq_id));
+        }
+        let v1 = self.node1.node_id(&self.identifer)?;
+        let v2 = self.node2.node_id(&self.identifer)?;
+        equation[v1] = Complex::from(1.0);
+        equation[v2] = Complex::from(-1.0);
+        equation[offset] = Complex::from(-self.resistance);
+        Ok(Complex::from(0.0))
+    }
+
+    fn connection_types(&self) -> &'static [ConnectionType] {
+        &[Anode, Cathode]
+    }
+
+    fn branches(&self) -> Vec<Branch> {
+        vec![Branch::new(Conductive, Anode, Cathode)]
+    }
+
+    fn sweep_value(&self) -> Result<f64, CircuitError> {
+        Ok(self.resistance)
+    }
+
+    fn set_sweep_value(&mut self, value: f64) -> Result<(), CircuitError> {
+        self.resistance = value;
+        Ok(())
     }
 }
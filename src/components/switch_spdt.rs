@@ -1,13 +1,19 @@
+use nalgebra::Complex;
+use serde::{Deserialize, Serialize};
+
 use std::ops::Not;
 
 use super::{
+    Branch,
+    BranchKind::VoltageDefined,
     ComponentTrait, Connection,
     Connection::*,
     ConnectionType::{self, *},
     Identifer,
 };
+use crate::error::CircuitError;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum SwitchPosition {
     LeftPosition,
     RightPosition,
@@ -25,7 +31,7 @@ impl Not for SwitchPosition {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SwitchSPDT {
     pub identifer: Identifer,
     pub state: SwitchPosition,
@@ -53,10 +59,10 @@ impl SwitchSPDT {
         self.state = state;
     }
 
-    pub fn get_output_id(&self) -> usize {
+    pub fn get_output_id(&self) -> Result<usize, CircuitError> {
         match self.state {
-            SwitchPosition::LeftPosition => self.node_l.get_id(),
-            SwitchPosition::RightPosition => self.node_r.get_id(),
+            SwitchPosition::LeftPosition => self.node_l.node_id(&self.identifer),
+            SwitchPosition::RightPosition => self.node_r.node_id(&self.identifer),
         }
     }
 
@@ -68,13 +74,11 @@ impl SwitchSPDT {
         }
     }
 
-    pub fn get_input_id(&self) -> usize {
-        self.node_m.get_id()
+    pub fn get_input_id(&self) -> Result<usize, CircuitError> {
+        self.node_m.node_id(&self.identifer)
     }
 }
 
-const PANIC_TEXT: &'static str = "SPDT Switch can only has connection type Left Right and Middle";
-
 impl ComponentTrait for SwitchSPDT {
     fn get_id(&self) -> usize {
         self.identifer.id
@@ -84,34 +88,45 @@ impl ComponentTrait for SwitchSPDT {
         self.identifer.name.clone()
     }
 
-    fn connect(&mut self, node: usize, connection_type: ConnectionType) {
+    fn connect(
+        &mut self,
+        node: usize,
+        connection_type: ConnectionType,
+    ) -> Result<(), CircuitError> {
         match connection_type {
             Left => self.node_l = Connected(node, Left),
             Middle => self.node_m = Connected(node, Middle),
             Right => self.node_r = Connected(node, Right),
-            _ => unimplemented!("{PANIC_TEXT}"),
-        }
+            _ => return Err(CircuitError::invalid_pin(&self.identifer, connection_type)),
+        };
+        Ok(())
     }
 
-    fn disconnect(&mut self, connection_type: ConnectionType) {
+    fn disconnect(&mut self, connection_type: ConnectionType) -> Result<(), CircuitError> {
         match connection_type {
             Left => self.node_l = Disconnected(Left),
             Middle => self.node_m = Disconnected(Middle),
             Right => self.node_r = Disconnected(Right),
-            _ => unimplemented!("{PANIC_TEXT}"),
-        }
+            _ => return Err(CircuitError::invalid_pin(&self.identifer, connection_type)),
+        };
+        Ok(())
     }
 
-    fn get_connection(&self, connection_type: ConnectionType) -> Connection {
+    fn get_connection(&self, connection_type: ConnectionType) -> Result<Connection, CircuitError> {
         match connection_type {
-            Left => self.node_l.clone(),
-            Middle => self.node_m.clone(),
-            Right => self.node_r.clone(),
-            _ => unimplemented!("{PANIC_TEXT}"),
+            Left => Ok(self.node_l),
+            Middle => Ok(self.node_m),
+            Right => Ok(self.node_r),
+            _ => Err(CircuitError::invalid_pin(&self.identifer, connection_type)),
         }
     }
 
-    fn current_representative(&self, index: usize, conn_type: ConnectionType, eq: &mut [f64]) {
+    fn current_representative(
+        &self,
+        index: usize,
+        conn_type: ConnectionType,
+        eq: &mut [f64],
+    ) -> Result<(), CircuitError> {
         // This is complex, depending on which connection that asks and the state of the switch
         // we return different currents to the node
         // current 0 is the current that goes middle to left
@@ -127,33 +142,77 @@ impl ComponentTrait for SwitchSPDT {
             Right => {
                 eq[index + 1] = 1.0; // current flows out of the right "output"
             }
-            _ => unimplemented!("{PANIC_TEXT}"),
-        }
+            _ => return Err(CircuitError::invalid_pin(&self.identifer, conn_type)),
+        };
+        Ok(())
     }
 
     fn num_eq(&self) -> usize {
         2
     }
 
-    fn equation(&self, offset: usize, equation: &mut [f64], eq_id: usize) -> f64 {
+    fn equation(
+        &self,
+        offset: usize,
+        equation: &mut [f64],
+        eq_id: usize,
+    ) -> Result<f64, CircuitError> {
         // This is complex, we have two equations:
         // 1. the potential of the middle node is the same as the selected node
         // 2. the current into the unselected node is 0
-        assert!(eq_id < self.num_eq());
+        if eq_id >= self.num_eq() {
+            return Err(CircuitError::invalid_equation(&self.identifer, eq_id));
+        }
         if eq_id == 0 {
             // Potential on each side of the switch selected is the same
             // v0 - v1 = 0
-            let v0 = self.get_input_id();
-            let v1 = self.get_output_id();
+            let v0 = self.get_input_id()?;
+            let v1 = self.get_output_id()?;
             equation[v0] = 1.0;
             equation[v1] = -1.0;
-            0.0
+            Ok(0.0)
         } else {
             // Current into the unselected node is 0
             // i0 = 0
             let i0 = self.get_unused_offset();
             equation[offset + i0] = 1.0;
-            0.0
+            Ok(0.0)
+        }
+    }
+
+    fn ac_equation(
+        &self,
+        offset: usize,
+        equation: &mut [Complex<f64>],
+        eq_id: usize,
+        _omega: f64,
+    ) -> Result<Complex<f64>, CircuitError> {
+        // The switch is purely resistive so the equations are the same as in DC
+        if eq_id >= self.num_eq() {
+            return Err(CircuitError::invalid_equation(&self.identifer, eq_id));
         }
+        if eq_id == 0 {
+            let v0 = self.get_input_id()?;
+            let v1 = self.get_output_id()?;
+            equation[v0] = Complex::from(1.0);
+            equation[v1] = Complex::from(-1.0);
+        } else {
+            let i0 = self.get_unused_offset();
+            equation[offset + i0] = Complex::from(1.0);
+        }
+        Ok(Complex::from(0.0))
+    }
+
+    fn connection_types(&self) -> &'static [ConnectionType] {
+        &[Left, Middle, Right]
+    }
+
+    fn branches(&self) -> Vec<Branch> {
+        // only the selected side of the switch is connected to the middle
+        let selected = match self.state {
+            SwitchPosition::LeftPosition => Left,
+            SwitchPosition::RightPosition => Right,
+        };
+        vec![Branch::new(VoltageDefined, Middle, selected)]
     }
 }
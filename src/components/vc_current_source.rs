@@ -0,0 +1,166 @@
+use nalgebra::Complex;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    Branch,
+    BranchKind::CurrentDefined,
+    ComponentTrait, Connection,
+    Connection::*,
+    ConnectionType::{self, *},
+    Identifer,
+};
+use crate::error::CircuitError;
+
+/// This struct represents a voltage controlled current source (SPICE G element) in a circuit.
+/// The current flowing into Pin0 (out+), through the source and out of Pin1 (out-) is
+/// transconductance times the voltage from Pin2 (ctl+) to Pin3 (ctl-).
+/// No current flows through the control pins.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct VCCurrentSource {
+    pub identifer: Identifer,
+    pub transconductance: f64,
+    pub out_pos: Connection,
+    pub out_neg: Connection,
+    pub ctl_pos: Connection,
+    pub ctl_neg: Connection,
+}
+
+impl std::fmt::Debug for VCCurrentSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "G ({}) gm:{} Out+: {:?} Out-: {:?} Ctl+: {:?} Ctl-: {:?}",
+            self.identifer.id,
+            self.transconductance,
+            self.out_pos,
+            self.out_neg,
+            self.ctl_pos,
+            self.ctl_neg
+        )
+    }
+}
+
+impl VCCurrentSource {
+    pub fn new(identifer: Identifer, transconductance: f64) -> Self {
+        Self {
+            identifer,
+            transconductance,
+            out_pos: Disconnected(Pin0),
+            out_neg: Disconnected(Pin1),
+            ctl_pos: Disconnected(Pin2),
+            ctl_neg: Disconnected(Pin3),
+        }
+    }
+}
+
+impl ComponentTrait for VCCurrentSource {
+    fn get_id(&self) -> usize {
+        self.identifer.id
+    }
+
+    fn get_name(&self) -> String {
+        self.identifer.name.clone()
+    }
+
+    fn connect(
+        &mut self,
+        node: usize,
+        connection_type: ConnectionType,
+    ) -> Result<(), CircuitError> {
+        match connection_type {
+            Pin0 => self.out_pos = Connected(node, Pin0),
+            Pin1 => self.out_neg = Connected(node, Pin1),
+            Pin2 => self.ctl_pos = Connected(node, Pin2),
+            Pin3 => self.ctl_neg = Connected(node, Pin3),
+            _ => return Err(CircuitError::invalid_pin(&self.identifer, connection_type)),
+        };
+        Ok(())
+    }
+
+    fn disconnect(&mut self, connection_type: ConnectionType) -> Result<(), CircuitError> {
+        match connection_type {
+            Pin0 => self.out_pos = Disconnected(Pin0),
+            Pin1 => self.out_neg = Disconnected(Pin1),
+            Pin2 => self.ctl_pos = Disconnected(Pin2),
+            Pin3 => self.ctl_neg = Disconnected(Pin3),
+            _ => return Err(CircuitError::invalid_pin(&self.identifer, connection_type)),
+        };
+        Ok(())
+    }
+
+    fn get_connection(&self, connection_type: ConnectionType) -> Result<Connection, CircuitError> {
+        match connection_type {
+            Pin0 => Ok(self.out_pos),
+            Pin1 => Ok(self.out_neg),
+            Pin2 => Ok(self.ctl_pos),
+            Pin3 => Ok(self.ctl_neg),
+            _ => Err(CircuitError::invalid_pin(&self.identifer, connection_type)),
+        }
+    }
+
+    fn current_representative(
+        &self,
+        index: usize,
+        conn_type: ConnectionType,
+        eq: &mut [f64],
+    ) -> Result<(), CircuitError> {
+        match conn_type {
+            Pin0 => eq[index] = -1.0, // current flows out of the node
+            Pin1 => eq[index] = 1.0,  // current flows into the node
+            Pin2 | Pin3 => {}         // the control pins do not draw any current
+            _ => return Err(CircuitError::invalid_pin(&self.identifer, conn_type)),
+        };
+        Ok(())
+    }
+
+    fn num_eq(&self) -> usize {
+        1
+    }
+
+    fn equation(
+        &self,
+        offset: usize,
+        equation: &mut [f64],
+        eq_id: usize,
+    ) -> Result<f64, CircuitError> {
+        // I = gm (V2 - V3)
+        // I - gm V2 + gm V3 = 0
+        if eq_id >= self.num_eq() {
+            return Err(CircuitError::invalid_equation(&self.identifer, eq_id));
+        }
+        let v2 = self.ctl_pos.node_id(&self.identifer)?;
+        let v3 = self.ctl_neg.node_id(&self.identifer)?;
+        equation[offset] = 1.0;
+        equation[v2] -= self.transconductance;
+        equation[v3] += self.transconductance;
+        Ok(0.0)
+    }
+
+    fn ac_equation(
+        &self,
+        offset: usize,
+        equation: &mut [Complex<f64>],
+        eq_id: usize,
+        _omega: f64,
+    ) -> Result<Complex<f64>, CircuitError> {
+        // I - gm V2 + gm V3 = 0
+        if eq_id >= self.num_eq() {
+            return Err(CircuitError::invalid_equation(&self.identifer, eq_id));
+        }
+        let v2 = self.ctl_pos.node_id(&self.identifer)?;
+        let v3 = self.ctl_neg.node_id(&self.identifer)?;
+        equation[offset] = Complex::from(1.0);
+        equation[v2] -= self.transconductance;
+        equation[v3] += self.transconductance;
+        Ok(Complex::from(0.0))
+    }
+
+    fn connection_types(&self) -> &'static [ConnectionType] {
+        &[Pin0, Pin1, Pin2, Pin3]
+    }
+
+    fn branches(&self) -> Vec<Branch> {
+        // the control pins only sense a voltage
+        vec![Branch::new(CurrentDefined, Pin0, Pin1)]
+    }
+}
@@ -0,0 +1,162 @@
+use nalgebra::Complex;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    Branch,
+    BranchKind::VoltageDefined,
+    ComponentTrait, Connection,
+    Connection::*,
+    ConnectionType::{self, *},
+    Identifer,
+};
+use crate::error::CircuitError;
+
+/// This struct represents a voltage controlled voltage source (SPICE E element) in a circuit.
+/// The voltage from Pin0 (out+) to Pin1 (out-) is gain times the voltage from Pin2 (ctl+) to Pin3 (ctl-).
+/// Its current flows into out+ and out of out-, no current flows through the control pins.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct VCVoltageSource {
+    pub identifer: Identifer,
+    pub gain: f64,
+    pub out_pos: Connection,
+    pub out_neg: Connection,
+    pub ctl_pos: Connection,
+    pub ctl_neg: Connection,
+}
+
+impl std::fmt::Debug for VCVoltageSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "E ({}) gain:{} Out+: {:?} Out-: {:?} Ctl+: {:?} Ctl-: {:?}",
+            self.identifer.id, self.gain, self.out_pos, self.out_neg, self.ctl_pos, self.ctl_neg
+        )
+    }
+}
+
+impl VCVoltageSource {
+    pub fn new(identifer: Identifer, gain: f64) -> Self {
+        Self {
+            identifer,
+            gain,
+            out_pos: Disconnected(Pin0),
+            out_neg: Disconnected(Pin1),
+            ctl_pos: Disconnected(Pin2),
+            ctl_neg: Disconnected(Pin3),
+        }
+    }
+}
+
+impl ComponentTrait for VCVoltageSource {
+    fn get_id(&self) -> usize {
+        self.identifer.id
+    }
+
+    fn get_name(&self) -> String {
+        self.identifer.name.clone()
+    }
+
+    fn connect(
+        &mut self,
+        node: usize,
+        connection_type: ConnectionType,
+    ) -> Result<(), CircuitError> {
+        match connection_type {
+            Pin0 => self.out_pos = Connected(node, Pin0),
+            Pin1 => self.out_neg = Connected(node, Pin1),
+            Pin2 => self.ctl_pos = Connected(node, Pin2),
+            Pin3 => self.ctl_neg = Connected(node, Pin3),
+            _ => return Err(CircuitError::invalid_pin(&self.identifer, connection_type)),
+        };
+        Ok(())
+    }
+
+    fn disconnect(&mut self, connection_type: ConnectionType) -> Result<(), CircuitError> {
+        match connection_type {
+            Pin0 => self.out_pos = Disconnected(Pin0),
+            Pin1 => self.out_neg = Disconnected(Pin1),
+            Pin2 => self.ctl_pos = Disconnected(Pin2),
+            Pin3 => self.ctl_neg = Disconnected(Pin3),
+            _ => return Err(CircuitError::invalid_pin(&self.identifer, connection_type)),
+        };
+        Ok(())
+    }
+
+    fn get_connection(&self, connection_type: ConnectionType) -> Result<Connection, CircuitError> {
+        match connection_type {
+            Pin0 => Ok(self.out_pos),
+            Pin1 => Ok(self.out_neg),
+            Pin2 => Ok(self.ctl_pos),
+            Pin3 => Ok(self.ctl_neg),
+            _ => Err(CircuitError::invalid_pin(&self.identifer, connection_type)),
+        }
+    }
+
+    fn current_representative(
+        &self,
+        index: usize,
+        conn_type: ConnectionType,
+        eq: &mut [f64],
+    ) -> Result<(), CircuitError> {
+        match conn_type {
+            Pin0 => eq[index] = -1.0, // current flows out of the node
+            Pin1 => eq[index] = 1.0,  // current flows into the node
+            Pin2 | Pin3 => {}         // the control pins do not draw any current
+            _ => return Err(CircuitError::invalid_pin(&self.identifer, conn_type)),
+        };
+        Ok(())
+    }
+
+    fn num_eq(&self) -> usize {
+        1
+    }
+
+    fn equation(&self, _: usize, equation: &mut [f64], eq_id: usize) -> Result<f64, CircuitError> {
+        // V0 - V1 = gain (V2 - V3)
+        // V0 - V1 - gain V2 + gain V3 = 0
+        // the pins can share nodes so the coefficients are added up
+        if eq_id >= self.num_eq() {
+            return Err(CircuitError::invalid_equation(&self.identifer, eq_id));
+        }
+        let v0 = self.out_pos.node_id(&self.identifer)?;
+        let v1 = self.out_neg.node_id(&self.identifer)?;
+        let v2 = self.ctl_pos.node_id(&self.identifer)?;
+        let v3 = self.ctl_neg.node_id(&self.identifer)?;
+        equation[v0] += 1.0;
+        equation[v1] -= 1.0;
+        equation[v2] -= self.gain;
+        equation[v3] += self.gain;
+        Ok(0.0)
+    }
+
+    fn ac_equation(
+        &self,
+        _: usize,
+        equation: &mut [Complex<f64>],
+        eq_id: usize,
+        _omega: f64,
+    ) -> Result<Complex<f64>, CircuitError> {
+        // V0 - V1 - gain V2 + gain V3 = 0
+        if eq_id >= self.num_eq() {
+            return Err(CircuitError::invalid_equation(&self.identifer, eq_id));
+        }
+        let v0 = self.out_pos.node_id(&self.identifer)?;
+        let v1 = self.out_neg.node_id(&self.identifer)?;
+        let v2 = self.ctl_pos.node_id(&self.identifer)?;
+        let v3 = self.ctl_neg.node_id(&self.identifer)?;
+        equation[v0] += 1.0;
+        equation[v1] -= 1.0;
+        equation[v2] -= self.gain;
+        equation[v3] += self.gain;
+        Ok(Complex::from(0.0))
+    }
+
+    fn connection_types(&self) -> &'static [ConnectionType] {
+        &[Pin0, Pin1, Pin2, Pin3]
+    }
+
+    fn branches(&self) -> Vec<Branch> {
+        // the control pins only sense a voltage
+        vec![Branch::new(VoltageDefined, Pin1, Pin0)]
+    }
+}
@@ -0,0 +1,116 @@
+use std::fmt;
+
+use crate::components::{ConnectionType, Identifer};
+use crate::validation::TopologyIssue;
+
+/// Everything that can go wrong while building or solving a circuit.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CircuitError {
+    // a component does not have a pin of this connection type
+    InvalidPin {
+        component: String,
+        pin: ConnectionType,
+    },
+    // a pin of a component that has to be connected is not
+    DisconnectedPin {
+        component: String,
+        pin: ConnectionType,
+    },
+    // a component was asked for an equation it does not have
+    InvalidEquation {
+        component: String,
+        eq_id: usize,
+    },
+    ComponentOutOfRange(usize),
+    NodeOutOfRange(usize),
+    // there is no component or node with this name
+    UnknownComponent(String),
+    UnknownNode(String),
+    // two pins that are already connected to different nodes were connected to each other
+    ConflictingNodes(usize, usize),
+    SingularMatrix,
+    AlreadyLocked,
+    NotLocked,
+    InvalidParameter(String),
+    InvalidTopology(Vec<TopologyIssue>),
+    // the Newton-Raphson iteration did not converge in the given number of iterations
+    NoConvergence(usize),
+    // an equation has an entry in a column its component did not declare, see ComponentTrait::equation
+    InvalidStamp {
+        row: usize,
+        column: usize,
+    },
+    // a JSON document could not be read, or has an unsupported schema version
+    InvalidJson(String),
+}
+
+impl CircuitError {
+    pub fn invalid_pin(identifer: &Identifer, pin: ConnectionType) -> Self {
+        CircuitError::InvalidPin {
+            component: identifer.name.clone(),
+            pin,
+        }
+    }
+
+    pub fn invalid_equation(identifer: &Identifer, eq_id: usize) -> Self {
+        CircuitError::InvalidEquation {
+            component: identifer.name.clone(),
+            eq_id,
+        }
+    }
+
+    pub fn disconnected_pin(identifer: &Identifer, pin: ConnectionType) -> Self {
+        CircuitError::DisconnectedPin {
+            component: identifer.name.clone(),
+            pin,
+        }
+    }
+}
+
+impl fmt::Display for CircuitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CircuitError::InvalidPin { component, pin } => {
+                write!(f, "{component} has no pin of connection type {pin:?}")
+            }
+            CircuitError::DisconnectedPin { component, pin } => {
+                write!(f, "pin {pin:?} of {component} is not connected")
+            }
+            CircuitError::InvalidEquation { component, eq_id } => {
+                write!(f, "{component} has no equation {eq_id}")
+            }
+            CircuitError::ComponentOutOfRange(id) => write!(f, "there is no component {id}"),
+            CircuitError::NodeOutOfRange(id) => write!(f, "there is no node {id}"),
+            CircuitError::UnknownComponent(name) => write!(f, "there is no component named {name}"),
+            CircuitError::UnknownNode(name) => write!(f, "there is no node named {name}"),
+            CircuitError::ConflictingNodes(node1, node2) => write!(
+                f,
+                "both pins are already connected to different nodes ({node1} and {node2})"
+            ),
+            CircuitError::SingularMatrix => {
+                write!(
+                    f,
+                    "the system of equations is singular and has no unique solution"
+                )
+            }
+            CircuitError::AlreadyLocked => write!(f, "the circuit is already locked"),
+            CircuitError::NotLocked => write!(f, "the circuit has to be locked first"),
+            CircuitError::InvalidParameter(message) => write!(f, "{message}"),
+            CircuitError::InvalidTopology(issues) => {
+                let issues: Vec<String> = issues.iter().map(|i| i.to_string()).collect();
+                write!(f, "invalid circuit topology: {}", issues.join("; "))
+            }
+            CircuitError::NoConvergence(iterations) => write!(
+                f,
+                "the Newton-Raphson iteration did not converge in {iterations} iterations"
+            ),
+            CircuitError::InvalidStamp { row, column } => write!(
+                f,
+                "equation {row} has an entry in column {column}, which it does not refer to"
+            ),
+            CircuitError::InvalidJson(message) => write!(f, "invalid JSON document: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for CircuitError {}
@@ -0,0 +1,121 @@
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::analysis::{
+    AcResult, DcSweepResult, MeshResult, PowerReport, SuperpositionResult, TheveninEquivalent,
+    TransientResult,
+};
+use crate::circuit::Circuit;
+use crate::error::CircuitError;
+use crate::solution::Solution;
+
+/// bumped whenever a change to the serialized types breaks reading older documents
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// A value that can be stored as a JSON document.
+/// kind tells the documents apart, restore rebuilds what is derived from the stored data.
+pub trait Document: Serialize + DeserializeOwned {
+    const KIND: &'static str;
+
+    fn restore(&mut self) -> Result<(), CircuitError> {
+        Ok(())
+    }
+}
+
+/*
+* The envelope every document is stored in, so readers can check what they are reading.
+*/
+#[derive(Serialize, Deserialize)]
+struct Envelope<T> {
+    version: u32,
+    kind: String,
+    data: T,
+}
+
+impl Document for Circuit {
+    const KIND: &'static str = "circuit";
+
+    fn restore(&mut self) -> Result<(), CircuitError> {
+        // a locked circuit is checked again, the file could have been edited by hand
+        if self.is_locked() {
+            self.unlock();
+            self.lock()?;
+        }
+        Ok(())
+    }
+}
+
+impl Document for Solution {
+    const KIND: &'static str = "solution";
+
+    fn restore(&mut self) -> Result<(), CircuitError> {
+        self.restore_circuit()
+    }
+}
+
+impl Document for TransientResult {
+    const KIND: &'static str = "transient";
+}
+
+impl Document for AcResult {
+    const KIND: &'static str = "ac";
+}
+
+impl Document for DcSweepResult {
+    const KIND: &'static str = "dc_sweep";
+}
+
+impl Document for MeshResult {
+    const KIND: &'static str = "mesh";
+}
+
+impl Document for PowerReport {
+    const KIND: &'static str = "power";
+}
+
+impl Document for SuperpositionResult {
+    const KIND: &'static str = "superposition";
+}
+
+impl Document for TheveninEquivalent {
+    const KIND: &'static str = "thevenin";
+}
+
+pub fn to_json<T: Document>(value: &T) -> Result<String, CircuitError> {
+    let envelope = Envelope {
+        version: SCHEMA_VERSION,
+        kind: T::KIND.to_string(),
+        data: value,
+    };
+    serde_json::to_string_pretty(&envelope).map_err(|e| CircuitError::InvalidJson(e.to_string()))
+}
+
+/// Reads a document written by to_json, it has to be of the same kind and schema version.
+pub fn from_json<T: Document>(text: &str) -> Result<T, CircuitError> {
+    #[derive(Deserialize)]
+    struct Header {
+        version: u32,
+        kind: String,
+    }
+
+    let header: Header =
+        serde_json::from_str(text).map_err(|e| CircuitError::InvalidJson(e.to_string()))?;
+    if header.version != SCHEMA_VERSION {
+        return Err(CircuitError::InvalidJson(format!(
+            "schema version {} is not supported, expected {SCHEMA_VERSION}",
+            header.version
+        )));
+    }
+    if header.kind != T::KIND {
+        return Err(CircuitError::InvalidJson(format!(
+            "expected a {} document, found a {} document",
+            T::KIND,
+            header.kind
+        )));
+    }
+    let envelope: Envelope<T> =
+        serde_json::from_str(text).map_err(|e| CircuitError::InvalidJson(e.to_string()))?;
+    let mut value = envelope.data;
+    value.restore()?;
+    Ok(value)
+}
@@ -0,0 +1,27 @@
+/*!
+* A circuit simulator based on modified nodal analysis.
+*
+* A Circuit is built from components and nodes, or parsed from a netlist, and locked before it
+* is handed to a Solver. The Solver finds the operating point as a Solution and runs the
+* transient, AC and DC sweep analyses. Circuits and results can be stored as JSON documents.
+* A Shell builds and solves a circuit interactively, command by command, and the cli module
+* holds the demo circuit and the reports printed by the rustic binary.
+*/
+
+pub mod analysis;
+pub mod circuit;
+pub mod cli;
+pub mod components;
+pub mod error;
+pub mod json;
+pub mod netlist;
+pub mod repl;
+pub mod solution;
+pub mod solver;
+mod sparse;
+pub mod validation;
+
+pub use circuit::Circuit;
+pub use error::CircuitError;
+pub use solution::Solution;
+pub use solver::Solver;
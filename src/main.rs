@@ -1,92 +1,143 @@
-#![allow(dead_code)]
-
-mod circuit;
-mod components;
-mod solver;
-
-use circuit::Circuit;
-use components::ConnectionType::{Anode, Cathode, GroundConnection, *};
-use components::*;
-use solver::Solver;
-
-fn new_identifer(id: &mut usize) -> Identifer {
-    let res = components::Identifer::from_id(*id);
-    *id += 1;
-    return res;
-}
+use rustic::analysis::{AcSweep, DcSweep};
+use rustic::components::IntegrationMethod;
+use rustic::{cli, json, netlist, repl, CircuitError, Solution, Solver};
 
 fn main() {
-    // just build a simple circuit for now
-    // this process will be improved at some point
-    let mut a: usize = 0;
-    let component_id = &mut a;
-
-    let mut components = vec![
-        DCVoltageSourceComponent(DCVoltageSource::new(new_identifer(component_id), 3.0)),
-        ResistorComponent(Resistor::new(new_identifer(component_id), 1000.0)),
-        ResistorComponent(Resistor::new(new_identifer(component_id), 2000.0)),
-        GroundComponent(Ground::new(new_identifer(component_id))),
-        SwitchSPDTComponent(SwitchSPDT::new(new_identifer(component_id))),
-        DCVoltageSourceComponent(DCVoltageSource::new(new_identifer(component_id), 3.0)),
-        ResistorComponent(Resistor::new(new_identifer(component_id), 10000.0)),
-        ResistorComponent(Resistor::new(new_identifer(component_id), 50000.0)),
-        SwitchSPDTComponent(SwitchSPDT::new(new_identifer(component_id))),
-    ];
-
-    if let SwitchSPDTComponent(s) = &mut components[4] {
-        s.toggle(); // optional switch toggle (it works!)
-    } else {
-        panic!();
-    }
-    if let SwitchSPDTComponent(s) = &mut components[4 + 4] {
-        s.toggle(); // optional switch toggle (it works!)
-    } else {
-        panic!();
+    // a netlist file can be given as the first argument, otherwise the demo circuit is solved
+    // rustic <netlist> tran <t_step> <t_stop> [be|trap] runs a transient analysis instead
+    // rustic <netlist> ac <lin|dec|oct> <points> <f_start> <f_stop> runs an AC sweep instead
+    // rustic <netlist> dc <component> <start> <stop> <step> sweeps the value of a source or resistor
+    // rustic <netlist> thevenin <node+> <node-> prints the equivalent circuit between two nodes
+    // rustic <netlist> superposition prints what every independent source contributes
+    // rustic <netlist> mesh solves for loop currents and compares them with nodal analysis
+    // rustic <netlist> json [circuit|solution] prints the circuit or its solution as a JSON document,
+    // files ending in .json are loaded as circuit documents
+    // rustic repl starts an interactive shell to build and solve a circuit
+    // --matrix prints the system of equations of the operating point before the solution
+    let mut args: Vec<String> = std::env::args().collect();
+    let print_matrix = args.iter().any(|arg| arg == "--matrix");
+    args.retain(|arg| arg != "--matrix");
+    if args.get(1).map(String::as_str) == Some("repl") {
+        if let Err(e) = repl::Shell::new().run() {
+            eprintln!("Failed to read from the terminal: {e}");
+            std::process::exit(1);
+        }
+        return;
     }
-
-    let mut circuit = Circuit::from_components("test".to_string(), 0, components);
-
-    // ((component_id, connection_type) (component_id, connection_type))
-    // let connection_pairs = vec![
-    //     ((0, Anode), (1, Cathode)),
-    //     ((1, Anode), (2, Cathode)),
-    //     ((0, Anode), (3, GroundConnection)),
-    //     ((2, Anode), (0, Cathode)),
-    // ];
-    let connection_pairs = vec![
-        ((0, Anode), (1, Cathode)),
-        ((0, Anode), (2, Cathode)),
-        ((1, Anode), (4, Left)),
-        ((2, Anode), (4, Right)),
-        ((4, Middle), (0, Cathode)),
-        ((3, GroundConnection), (0, Anode)),
-        ((3, GroundConnection), (5, Cathode)),
-        ((0 + 5, Anode), (1 + 5, Cathode)),
-        ((0 + 5, Anode), (2 + 5, Cathode)),
-        ((1 + 5, Anode), (4 + 4, Left)),
-        ((2 + 5, Anode), (4 + 4, Right)),
-        ((4 + 4, Middle), (0 + 5, Cathode)),
-    ];
-    circuit.connect_components(connection_pairs);
-    circuit.lock();
+    let circuit = match args.get(1) {
+        Some(path) => cli::read_circuit(path).unwrap_or_else(|message| {
+            eprintln!("{message}");
+            std::process::exit(1);
+        }),
+        None => cli::demo_circuit()
+            .unwrap_or_else(|e| exit_with_error("Failed to build the demo circuit", e)),
+    };
 
     let mut nvm = Solver::new(circuit);
-    nvm.solve().expect("Failed to solve circuit");
-
-    for (i, comp) in nvm.components().iter().enumerate() {
-        for passage in 0..comp.get_currents() {
-            println!(
-                "Component: {}.{passage}: {:.6}A",
-                comp.get_name(),
-                nvm.currents[i + passage],
-            );
+    let value = |i: usize, usage: &str| {
+        args.get(i)
+            .and_then(|v| netlist::parse_value(v))
+            .unwrap_or_else(|| {
+                eprintln!("usage: rustic <netlist> {usage}");
+                std::process::exit(1);
+            })
+    };
+    let report = match args.get(2).map(String::as_str) {
+        Some("tran") => {
+            let usage = "tran <t_step> <t_stop> [be|trap]";
+            let method = match args.get(5).map(String::as_str) {
+                Some("be") => IntegrationMethod::BackwardEuler,
+                _ => IntegrationMethod::Trapezoidal,
+            };
+            let result = nvm
+                .transient(value(4, usage), value(3, usage), method)
+                .unwrap_or_else(|e| exit_with_error("Failed to simulate circuit", e));
+            cli::format_waveforms(&nvm, &result)
         }
-    }
-    for i in 0..nvm.nodes().len() {
-        println!(
-            "Node: {}: {:.2}V",
-            nvm.get_node(i).get_name(),
-            nvm.potentials[i]
-        );
-    }
+        Some("ac") => {
+            let usage = "ac <lin|dec|oct> <points> <f_start> <f_stop>";
+            let points = value(4, usage) as usize;
+            let sweep = match args.get(3).map(String::as_str) {
+                Some("lin") => AcSweep::Linear(points),
+                Some("oct") => AcSweep::Octave(points),
+                _ => AcSweep::Decade(points),
+            };
+            let result = nvm
+                .ac(sweep, value(5, usage), value(6, usage))
+                .unwrap_or_else(|e| exit_with_error("Failed to solve circuit", e));
+            cli::format_ac(&nvm, &result)
+        }
+        Some("dc") => {
+            let usage = "dc <component> <start> <stop> <step>";
+            let sweep = DcSweep::Linear {
+                start: value(4, usage),
+                stop: value(5, usage),
+                step: value(6, usage),
+            };
+            let component = args.get(3).map(String::as_str).unwrap_or_default();
+            let result = nvm
+                .dc_sweep(component, &sweep)
+                .unwrap_or_else(|e| exit_with_error("Failed to solve circuit", e));
+            cli::format_dc_sweep(&nvm, &result)
+        }
+        Some("thevenin") => {
+            let (Some(positive), Some(negative)) = (args.get(3), args.get(4)) else {
+                eprintln!("usage: rustic <netlist> thevenin <node+> <node->");
+                std::process::exit(1);
+            };
+            let equivalent = nvm
+                .thevenin(positive, negative)
+                .unwrap_or_else(|e| exit_with_error("Failed to solve circuit", e));
+            cli::format_thevenin(&equivalent)
+        }
+        Some("superposition") => {
+            let result = nvm
+                .superposition()
+                .unwrap_or_else(|e| exit_with_error("Failed to solve circuit", e));
+            cli::format_superposition(&nvm, &result)
+        }
+        Some("mesh") => {
+            let result = nvm
+                .mesh()
+                .unwrap_or_else(|e| exit_with_error("Failed to solve circuit", e));
+            let nodal = nvm
+                .solve_system()
+                .and_then(|x| Solution::new(&nvm.circuit, x))
+                .unwrap_or_else(|e| exit_with_error("Failed to solve circuit", e));
+            cli::format_mesh(&result, &nodal)
+        }
+        Some("json") => {
+            let document = match args.get(3).map(String::as_str) {
+                Some("circuit") => json::to_json(&nvm.circuit),
+                _ => nvm
+                    .solve_system()
+                    .and_then(|x| Solution::new(&nvm.circuit, x))
+                    .and_then(|solution| json::to_json(&solution)),
+            };
+            document.unwrap_or_else(|e| exit_with_error("Failed to export circuit", e)) + "\n"
+        }
+        _ => {
+            let solution = nvm
+                .solve()
+                .cloned()
+                .unwrap_or_else(|e| exit_with_error("Failed to solve circuit", e));
+            let mut report = String::new();
+            if print_matrix {
+                report += &cli::format_system(&nvm)
+                    .unwrap_or_else(|e| exit_with_error("Failed to build the system", e));
+            }
+            report += &cli::format_solution(&solution)
+                .unwrap_or_else(|e| exit_with_error("Failed to print solution", e));
+            let power = solution
+                .power_report()
+                .unwrap_or_else(|e| exit_with_error("Failed to compute power", e));
+            report + &cli::format_power(&power)
+        }
+    };
+    print!("{report}");
+}
+
+fn exit_with_error(message: &str, error: CircuitError) -> ! {
+    eprintln!("{message}: {error}");
+    std::process::exit(1);
 }
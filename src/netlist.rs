@@ -0,0 +1,663 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::circuit::Circuit;
+use crate::components::ConnectionType::{self, *};
+use crate::components::*;
+use crate::error::CircuitError;
+
+/// An error produced while parsing a netlist.
+/// line and column are 1-based and point at the offending token, they are 0 when the error
+/// concerns the circuit as a whole.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl ParseError {
+    fn new(line: usize, column: usize, message: String) -> Self {
+        Self {
+            line,
+            column,
+            message,
+        }
+    }
+
+    fn at(token: &Token, message: String) -> Self {
+        Self::new(token.line, token.column, message)
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.line == 0 {
+            return write!(f, "{}", self.message);
+        }
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/*
+* A single whitespace separated word of the netlist and where it was found.
+*/
+#[derive(Debug, Clone, Copy)]
+struct Token<'a> {
+    text: &'a str,
+    line: usize,
+    column: usize,
+}
+
+/*
+* A card is one logical netlist line, continuation lines starting with '+'
+* are appended to the card before them.
+*/
+type Card<'a> = Vec<Token<'a>>;
+
+pub const GROUND_NAMES: [&str; 2] = ["0", "gnd"];
+/// The name of the ground component connected to the reference node. Element names start with
+/// a letter, so no card can take it.
+pub const GROUND_COMPONENT: &str = "$GND";
+
+/// Parses a SPICE style netlist into a locked circuit.
+///
+/// Supported cards:
+/// ```text
+///   Rname n1 n2 value            resistor, n1 is the anode and n2 the cathode
+///   Cname n1 n2 value [IC=v]     capacitor, optionally starting a transient with v volts from n1 to n2
+///   Lname n1 n2 value [IC=i]     inductor, optionally starting a transient with i amps from n1 to n2
+///   Dname na nk [IS=i] [N=n] [RS=r]
+///                                diode from the anode na to the cathode nk, with the saturation current,
+///                                emission coefficient and series resistance (default 1e-14, 1 and 0)
+///   Ename o+ o- c+ c- gain       voltage controlled voltage source, V(o+) - V(o-) = gain * (V(c+) - V(c-))
+///   Gname o+ o- c+ c- gm         voltage controlled current source, gm * (V(c+) - V(c-)) amps flow
+///                                from o+ through the source to o-
+///   Hname o+ o- Vname r          current controlled voltage source, V(o+) - V(o-) = r * I(Vname)
+///   Fname o+ o- Vname gain       current controlled current source, gain * I(Vname) amps flow
+///                                from o+ through the source to o-
+///                                I(Vname) flows from the first node of Vname through it to its second node
+///   Oname in+ in- out [GAIN=a] [VMIN=v] [VMAX=v]
+///                                operational amplifier with its output driven relative to ground,
+///                                ideal unless a gain or rails are given, the gain defaults to 1e5 then
+///   Xname n1 .. nk type [KEY=value ..]
+///                                a component of a type registered with register_component, the nodes
+///                                are connected to its pins in order, the parameters go to its factory
+///   Vname n+ n- [DC] value       DC voltage source, n+ is held at value volts above n-
+///   Iname n+ n- [DC] value       DC current source, value amps flow from n+ through the source to n-
+///                                both sources accept AC magnitude [phase] for AC analysis
+///   .title name                  sets the name of the circuit
+///   .end                         stops parsing, everything after it is ignored
+/// ```
+/// Lines starting with '*' are comments and ';' starts a comment for the rest of the line.
+/// Node 0 (or gnd) is the reference node and gets a Ground component named `$GND` connected to it.
+/// Component names are not case sensitive, R1 and r1 are the same name.
+pub fn parse(name: String, input: &str) -> Result<Circuit, ParseError> {
+    let mut builder = NetlistBuilder::new(name);
+    for card in cards(input) {
+        let first = card[0];
+        if first.text.starts_with('.') {
+            if first.text.eq_ignore_ascii_case(".end") {
+                break;
+            }
+            builder.directive(&card)?;
+        } else {
+            builder.element(&card)?;
+        }
+    }
+    let mut circuit = builder.circuit;
+    if let Err(e) = circuit.lock() {
+        // a topology error points at the card of the first component that causes it
+        let (line, column) = match &e {
+            CircuitError::InvalidTopology(issues) => issues
+                .iter()
+                .flat_map(|issue| issue.components())
+                .find_map(|name| {
+                    let key = name.to_ascii_uppercase();
+                    builder.component_names.get(&key).copied()
+                })
+                .unwrap_or((0, 0)),
+            _ => (0, 0),
+        };
+        return Err(ParseError::new(line, column, e.to_string()));
+    }
+    Ok(circuit)
+}
+
+fn tokenize(line: &str, line_number: usize) -> Vec<Token<'_>> {
+    // strip the trailing comment before splitting
+    let line = match line.find(';') {
+        Some(index) => &line[..index],
+        None => line,
+    };
+    let mut tokens = Vec::new();
+    let mut start = None;
+    for (index, c) in line
+        .char_indices()
+        .chain(std::iter::once((line.len(), ' ')))
+    {
+        match (start, c.is_whitespace()) {
+            (None, false) => start = Some(index),
+            (Some(s), true) => {
+                tokens.push(Token {
+                    text: &line[s..index],
+                    line: line_number,
+                    column: line[..s].chars().count() + 1,
+                });
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    tokens
+}
+
+fn cards(input: &str) -> Vec<Card<'_>> {
+    let mut cards: Vec<Card> = Vec::new();
+    for (i, line) in input.lines().enumerate() {
+        let mut tokens = tokenize(line, i + 1);
+        let Some(first) = tokens.first() else {
+            continue;
+        };
+        if first.text.starts_with('*') {
+            continue;
+        }
+        if let Some(rest) = first.text.strip_prefix('+') {
+            if let Some(card) = cards.last_mut() {
+                if rest.is_empty() {
+                    tokens.remove(0);
+                } else {
+                    tokens[0].text = rest;
+                    tokens[0].column += 1;
+                }
+                card.extend(tokens);
+                continue;
+            }
+        }
+        cards.push(tokens);
+    }
+    cards
+}
+
+/// Parses a SPICE number with an optional scale factor, eg. 4.7k, 10u, 1meg, 2e-3.
+/// Letters following the scale factor are ignored so units like 1kohm are accepted.
+pub fn parse_value(text: &str) -> Option<f64> {
+    let bytes = text.as_bytes();
+    let mut end = 0;
+    if end < bytes.len() && (bytes[end] == b'+' || bytes[end] == b'-') {
+        end += 1;
+    }
+    let digits_start = end;
+    while end < bytes.len() && (bytes[end].is_ascii_digit() || bytes[end] == b'.') {
+        end += 1;
+    }
+    if end == digits_start {
+        return None;
+    }
+    // an exponent is only an exponent if it is followed by digits
+    if end < bytes.len() && (bytes[end] == b'e' || bytes[end] == b'E') {
+        let mut exp_end = end + 1;
+        if exp_end < bytes.len() && (bytes[exp_end] == b'+' || bytes[exp_end] == b'-') {
+            exp_end += 1;
+        }
+        if exp_end < bytes.len() && bytes[exp_end].is_ascii_digit() {
+            while exp_end < bytes.len() && bytes[exp_end].is_ascii_digit() {
+                exp_end += 1;
+            }
+            end = exp_end;
+        }
+    }
+    let number: f64 = text[..end].parse().ok()?;
+
+    let suffix = text[end..].to_ascii_lowercase();
+    let scale = if suffix.starts_with("meg") {
+        1e6
+    } else if suffix.starts_with("mil") {
+        25.4e-6
+    } else {
+        match suffix.chars().next() {
+            Some('t') => 1e12,
+            Some('g') => 1e9,
+            Some('k') => 1e3,
+            Some('m') => 1e-3,
+            Some('u') | Some('µ') => 1e-6,
+            Some('n') => 1e-9,
+            Some('p') => 1e-12,
+            Some('f') => 1e-15,
+            Some(c) if c.is_ascii_alphabetic() => 1.0,
+            None => 1.0,
+            Some(_) => return None,
+        }
+    };
+    Some(number * scale)
+}
+
+/*
+* Keeps track of the circuit being built and which node each name refers to.
+*/
+struct NetlistBuilder {
+    circuit: Circuit,
+    node_ids: HashMap<String, usize>,
+    // the line and column of the card of every component by its name in upper case, names
+    // are not case sensitive like in SPICE
+    component_names: HashMap<String, (usize, usize)>,
+}
+
+impl NetlistBuilder {
+    fn new(name: String) -> Self {
+        Self {
+            circuit: Circuit::new(name, 0),
+            node_ids: HashMap::new(),
+            component_names: HashMap::new(),
+        }
+    }
+
+    fn next_identifer(&self, name: &str) -> Identifer {
+        let id = self.circuit.components.len();
+        Identifer::new(name.to_string(), id, name.to_string(), name.to_string())
+    }
+
+    fn node_id(&mut self, name: &str) -> Result<usize, CircuitError> {
+        if let Some(id) = self.node_ids.get(name) {
+            return Ok(*id);
+        }
+        let is_ground = GROUND_NAMES.iter().any(|g| name.eq_ignore_ascii_case(g));
+        let key = if is_ground { GROUND_NAMES[0] } else { name };
+        if let Some(id) = self.node_ids.get(key) {
+            return Ok(*id);
+        }
+
+        let id = self.circuit.add_named_node(key);
+        self.node_ids.insert(key.to_string(), id);
+        if is_ground {
+            let ground = Ground::new(self.next_identifer(GROUND_COMPONENT));
+            let ground_id = ground.identifer.id;
+            self.circuit.add_component(GroundComponent(ground));
+            self.circuit.connect_node(ground_id, id, GroundConnection)?;
+        }
+        Ok(id)
+    }
+
+    fn directive(&mut self, card: &Card) -> Result<(), ParseError> {
+        let first = card[0];
+        if first.text.eq_ignore_ascii_case(".title") {
+            let title: Vec<&str> = card[1..].iter().map(|t| t.text).collect();
+            self.circuit.name = title.join(" ");
+            return Ok(());
+        }
+        Err(ParseError::at(
+            &first,
+            format!("unknown directive '{}'", first.text),
+        ))
+    }
+
+    fn element(&mut self, card: &Card) -> Result<(), ParseError> {
+        let first = card[0];
+        let name = first.text;
+        if let Some((line, _)) = self.component_names.get(&name.to_ascii_uppercase()) {
+            return Err(ParseError::at(
+                &first,
+                format!("duplicate component name '{name}', first defined on line {line}"),
+            ));
+        }
+
+        let identifer = self.next_identifer(name);
+        let (component, pins) = match name.chars().next().map(|c| c.to_ascii_uppercase()) {
+            Some('R') => {
+                let nodes = expect_nodes(card, 2)?;
+                let value = expect_value(card, 3, "resistance")?;
+                expect_end(card, 4)?;
+                let resistor = Resistor::new(identifer, value);
+                (
+                    ResistorComponent(resistor),
+                    vec![(nodes[0], Anode), (nodes[1], Cathode)],
+                )
+            }
+            Some('C') => {
+                let nodes = expect_nodes(card, 2)?;
+                let value = expect_value(card, 3, "capacitance")?;
+                let mut capacitor = Capacitor::new(identifer, value);
+                if let Some(token) = card.get(4) {
+                    capacitor.set_initial_voltage(expect_initial_condition(token)?);
+                }
+                expect_end(card, 5)?;
+                (
+                    CapacitorComponent(capacitor),
+                    vec![(nodes[0], Anode), (nodes[1], Cathode)],
+                )
+            }
+            Some('L') => {
+                let nodes = expect_nodes(card, 2)?;
+                let value = expect_value(card, 3, "inductance")?;
+                let mut inductor = Inductor::new(identifer, value);
+                if let Some(token) = card.get(4) {
+                    inductor.set_initial_current(expect_initial_condition(token)?);
+                }
+                expect_end(card, 5)?;
+                (
+                    InductorComponent(inductor),
+                    vec![(nodes[0], Anode), (nodes[1], Cathode)],
+                )
+            }
+            Some('D') => {
+                let nodes = expect_nodes(card, 2)?;
+                let mut parameters = [("is", 1e-14), ("n", 1.0), ("rs", 0.0)];
+                for token in &card[3..] {
+                    expect_parameter(token, &mut parameters)?;
+                }
+                let [(_, is), (_, n), (_, rs)] = parameters;
+                if is <= 0.0 || n <= 0.0 || rs < 0.0 {
+                    return Err(ParseError::at(
+                        &first,
+                        format!("invalid diode parameters for '{name}'"),
+                    ));
+                }
+                (
+                    DiodeComponent(Diode::new(identifer, is, n, rs)),
+                    vec![(nodes[0], Anode), (nodes[1], Cathode)],
+                )
+            }
+            Some('E') => {
+                let nodes = expect_nodes(card, 4)?;
+                let gain = expect_value(card, 5, "gain")?;
+                expect_end(card, 6)?;
+                let pins = vec![
+                    (nodes[0], Pin0),
+                    (nodes[1], Pin1),
+                    (nodes[2], Pin2),
+                    (nodes[3], Pin3),
+                ];
+                (
+                    VCVoltageSourceComponent(VCVoltageSource::new(identifer, gain)),
+                    pins,
+                )
+            }
+            Some('G') => {
+                let nodes = expect_nodes(card, 4)?;
+                let transconductance = expect_value(card, 5, "transconductance")?;
+                expect_end(card, 6)?;
+                let pins = vec![
+                    (nodes[0], Pin0),
+                    (nodes[1], Pin1),
+                    (nodes[2], Pin2),
+                    (nodes[3], Pin3),
+                ];
+                let source = VCCurrentSource::new(identifer, transconductance);
+                (VCCurrentSourceComponent(source), pins)
+            }
+            Some('H') => {
+                let nodes = expect_nodes(card, 2)?;
+                let control = expect_name(card, 3, "controlling element")?;
+                let transresistance = expect_value(card, 4, "transresistance")?;
+                expect_end(card, 5)?;
+                let source = CCVoltageSource::new(identifer, transresistance, control);
+                (
+                    CCVoltageSourceComponent(source),
+                    vec![(nodes[0], Pin0), (nodes[1], Pin1)],
+                )
+            }
+            Some('F') => {
+                let nodes = expect_nodes(card, 2)?;
+                let control = expect_name(card, 3, "controlling element")?;
+                let gain = expect_value(card, 4, "gain")?;
+                expect_end(card, 5)?;
+                let source = CCCurrentSource::new(identifer, gain, control);
+                (
+                    CCCurrentSourceComponent(source),
+                    vec![(nodes[0], Pin0), (nodes[1], Pin1)],
+                )
+            }
+            Some('O') => {
+                let nodes = expect_nodes(card, 3)?;
+                let mut parameters = [
+                    ("gain", f64::INFINITY),
+                    ("vmin", f64::NEG_INFINITY),
+                    ("vmax", f64::INFINITY),
+                ];
+                for token in &card[4..] {
+                    expect_parameter(token, &mut parameters)?;
+                }
+                let [(_, gain), (_, v_min), (_, v_max)] = parameters;
+                let op_amp = if card.len() == 4 {
+                    OpAmp::new(identifer)
+                } else if gain > 0.0 && v_min < v_max {
+                    let gain = if gain.is_finite() { gain } else { 1e5 };
+                    OpAmp::finite(identifer, gain, v_min, v_max)
+                } else {
+                    return Err(ParseError::at(
+                        &first,
+                        format!("invalid operational amplifier parameters for '{name}'"),
+                    ));
+                };
+                let pins = vec![
+                    (nodes[0], NonInverting),
+                    (nodes[1], Inverting),
+                    (nodes[2], Output),
+                ];
+                (OpAmpComponent(op_amp), pins)
+            }
+            Some('V') => {
+                let nodes = expect_nodes(card, 2)?;
+                let value = expect_source(card, 3)?;
+                let mut source = DCVoltageSource::new(identifer, value.dc);
+                if let Some((magnitude, phase)) = value.ac {
+                    source.set_ac(magnitude, phase);
+                }
+                // the cathode is the positive terminal of the voltage source
+                let pins = vec![(nodes[0], Cathode), (nodes[1], Anode)];
+                (DCVoltageSourceComponent(source), pins)
+            }
+            Some('I') => {
+                let nodes = expect_nodes(card, 2)?;
+                let value = expect_source(card, 3)?;
+                let mut source = DCCurrentSource::new(identifer, value.dc);
+                if let Some((magnitude, phase)) = value.ac {
+                    source.set_ac(magnitude, phase);
+                }
+                // the current source pushes current out of its cathode
+                let pins = vec![(nodes[0], Anode), (nodes[1], Cathode)];
+                (DCCurrentSourceComponent(source), pins)
+            }
+            Some('X') => {
+                let custom = expect_custom(card)?;
+                let type_token = custom.component_type;
+                let nodes = custom.nodes;
+                let component = create_component(type_token.text, identifer, &custom.parameters)
+                    .map_err(|e| ParseError::at(&type_token, e.to_string()))?;
+                let pins = component.connection_types();
+                if pins.len() != nodes.len() {
+                    return Err(ParseError::at(
+                        &first,
+                        format!(
+                            "{name} has {} pins but {} nodes are given",
+                            pins.len(),
+                            nodes.len()
+                        ),
+                    ));
+                }
+                let pins = nodes.iter().copied().zip(pins.iter().copied()).collect();
+                (CustomComponent(component), pins)
+            }
+            _ => {
+                return Err(ParseError::at(
+                    &first,
+                    format!("unknown element type '{name}'"),
+                ))
+            }
+        };
+
+        let comp_id = component.get_id();
+        self.circuit.add_component(component);
+        self.component_names
+            .insert(name.to_ascii_uppercase(), (first.line, first.column));
+        for (node, con_type) in pins {
+            self.connect(comp_id, &node, con_type)?;
+        }
+        Ok(())
+    }
+
+    fn connect(
+        &mut self,
+        comp_id: usize,
+        node: &Token,
+        con_type: ConnectionType,
+    ) -> Result<(), ParseError> {
+        self.node_id(node.text)
+            .and_then(|node_id| self.circuit.connect_node(comp_id, node_id, con_type))
+            .map_err(|e| ParseError::at(node, e.to_string()))
+    }
+}
+
+fn missing(card: &Card, what: &str) -> ParseError {
+    let last = card[card.len() - 1];
+    let column = last.column + last.text.chars().count();
+    ParseError::new(
+        last.line,
+        column,
+        format!("{} is missing {what}", card[0].text),
+    )
+}
+
+fn expect_nodes<'a>(card: &'a Card, count: usize) -> Result<&'a [Token<'a>], ParseError> {
+    if card.len() <= count {
+        return Err(missing(card, &format!("{count} nodes")));
+    }
+    Ok(&card[1..=count])
+}
+
+fn expect_name(card: &Card, index: usize, what: &str) -> Result<String, ParseError> {
+    card.get(index)
+        .map(|token| token.text.to_string())
+        .ok_or_else(|| missing(card, &format!("a {what}")))
+}
+
+fn expect_value(card: &Card, index: usize, what: &str) -> Result<f64, ParseError> {
+    let Some(token) = card.get(index) else {
+        return Err(missing(card, &format!("a {what}")));
+    };
+    parse_value(token.text)
+        .ok_or_else(|| ParseError::at(token, format!("invalid {what} '{}'", token.text)))
+}
+
+/*
+* The DC value and optional AC magnitude and phase of an independent source.
+*/
+struct SourceValue {
+    dc: f64,
+    ac: Option<(f64, f64)>,
+}
+
+fn expect_source(card: &Card, index: usize) -> Result<SourceValue, ParseError> {
+    let mut dc = None;
+    let mut ac = None;
+    let mut index = index;
+    while let Some(token) = card.get(index) {
+        if token.text.eq_ignore_ascii_case("dc") && dc.is_none() {
+            dc = Some(expect_value(card, index + 1, "DC value")?);
+            index += 2;
+        } else if token.text.eq_ignore_ascii_case("ac") && ac.is_none() {
+            let magnitude = expect_value(card, index + 1, "AC magnitude")?;
+            // the phase is optional and defaults to 0 degrees
+            match card.get(index + 2).and_then(|t| parse_value(t.text)) {
+                Some(phase) => {
+                    ac = Some((magnitude, phase));
+                    index += 3;
+                }
+                None => {
+                    ac = Some((magnitude, 0.0));
+                    index += 2;
+                }
+            }
+        } else if dc.is_none() && ac.is_none() {
+            dc = Some(expect_value(card, index, "source value")?);
+            index += 1;
+        } else {
+            return Err(ParseError::at(
+                token,
+                format!("unexpected '{}'", token.text),
+            ));
+        }
+    }
+    if dc.is_none() && ac.is_none() {
+        return Err(missing(card, "a source value"));
+    }
+    Ok(SourceValue {
+        dc: dc.unwrap_or(0.0),
+        ac,
+    })
+}
+
+fn expect_initial_condition(token: &Token) -> Result<f64, ParseError> {
+    let value = match token.text.split_once('=') {
+        Some((key, value)) if key.eq_ignore_ascii_case("ic") => parse_value(value),
+        _ => {
+            return Err(ParseError::at(
+                token,
+                format!("unexpected '{}'", token.text),
+            ))
+        }
+    };
+    value
+        .ok_or_else(|| ParseError::at(token, format!("invalid initial condition '{}'", token.text)))
+}
+
+/*
+* Sets the value of a KEY=value token, the key has to be one of the given parameters.
+*/
+fn expect_parameter(token: &Token, parameters: &mut [(&str, f64)]) -> Result<(), ParseError> {
+    let unexpected = || ParseError::at(token, format!("unexpected '{}'", token.text));
+    let (key, value) = token.text.split_once('=').ok_or_else(unexpected)?;
+    let parameter = parameters
+        .iter_mut()
+        .find(|(name, _)| key.eq_ignore_ascii_case(name))
+        .ok_or_else(unexpected)?;
+    parameter.1 = parse_value(value)
+        .ok_or_else(|| ParseError::at(token, format!("invalid {key} '{value}'")))?;
+    Ok(())
+}
+
+/*
+* The type, nodes and KEY=value parameters of a custom component card.
+*/
+struct CustomCard<'a> {
+    component_type: Token<'a>,
+    nodes: &'a [Token<'a>],
+    parameters: Vec<(String, f64)>,
+}
+
+/*
+* The type of a custom component is the last word before its parameters.
+*/
+fn expect_custom<'a>(card: &'a Card) -> Result<CustomCard<'a>, ParseError> {
+    let positional = card.iter().take_while(|t| !t.text.contains('=')).count();
+    if positional < 2 {
+        return Err(missing(card, "a component type"));
+    }
+    let mut parameters = Vec::new();
+    for token in &card[positional..] {
+        let (key, value) = token
+            .text
+            .split_once('=')
+            .ok_or_else(|| ParseError::at(token, format!("unexpected '{}'", token.text)))?;
+        let value = parse_value(value)
+            .ok_or_else(|| ParseError::at(token, format!("invalid {key} '{value}'")))?;
+        parameters.push((key.to_ascii_lowercase(), value));
+    }
+    Ok(CustomCard {
+        component_type: card[positional - 1],
+        nodes: &card[1..positional - 1],
+        parameters,
+    })
+}
+
+fn expect_end(card: &Card, index: usize) -> Result<(), ParseError> {
+    match card.get(index) {
+        Some(token) => Err(ParseError::at(
+            token,
+            format!("unexpected '{}'", token.text),
+        )),
+        None => Ok(()),
+    }
+}
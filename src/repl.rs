@@ -0,0 +1,494 @@
+use std::fmt;
+
+use rustyline::error::ReadlineError;
+use rustyline::DefaultEditor;
+
+use crate::components::ConnectionType::{self, *};
+use crate::components::*;
+use crate::netlist::{self, GROUND_COMPONENT, GROUND_NAMES};
+use crate::{Circuit, CircuitError, Solution, Solver};
+
+const HISTORY_FILE: &str = ".rustic_history";
+
+const HELP: &str = "\
+commands:
+  add <name> [value]           adds a component, the first letter of the name picks the type:
+                               R resistor, C capacitor, L inductor, D diode, V voltage source,
+                               I current source, S switch (spdt), O ideal operational amplifier
+  connect <name>.<pin> <node>  connects a pin to a node, the node is created if it does not exist,
+                               node 0 (or gnd) is the reference node
+                               pins: anode, cathode, left, middle, right, in+, in-, out, pin0..pin3
+                               the cathode of a voltage source is its positive terminal
+  toggle <switch>              moves a switch to its other position
+  solve                        solves the operating point of the circuit
+  print components             lists the components and the nodes of their pins
+  print nodes                  the potentials of the last solution
+  print currents               the currents of the last solution
+  print power                  the power of every component in the last solution
+  thevenin <node+> <node->     the Thevenin and Norton equivalents of the circuit between two nodes
+  save <file>                  saves the commands that built the circuit
+  load <file>                  replaces the circuit with the one built by the commands in a file
+  clear                        starts over with an empty circuit
+  help                         shows this message
+  quit                         leaves the shell";
+
+/// An error in a command typed into the shell, shown to the user before the next prompt.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommandError(String);
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for CommandError {}
+
+impl From<CircuitError> for CommandError {
+    fn from(error: CircuitError) -> Self {
+        CommandError(error.to_string())
+    }
+}
+
+impl From<std::io::Error> for CommandError {
+    fn from(error: std::io::Error) -> Self {
+        CommandError(error.to_string())
+    }
+}
+
+/// An interactive shell that builds a circuit command by command and solves it.
+/// The circuit stays unlocked so it can be changed after solving, solve works on a locked copy.
+/// script holds the commands that changed the circuit, save writes them to a file.
+pub struct Shell {
+    circuit: Circuit,
+    solution: Option<Solution>,
+    script: Vec<String>,
+}
+
+impl Default for Shell {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Shell {
+    pub fn new() -> Self {
+        Self {
+            circuit: Circuit::new("shell".to_string(), 0),
+            solution: None,
+            script: Vec::new(),
+        }
+    }
+
+    /// The circuit built so far, it is not locked.
+    pub fn circuit(&self) -> &Circuit {
+        &self.circuit
+    }
+
+    /// The solution of the last solve, None if the circuit changed since.
+    pub fn solution(&self) -> Option<&Solution> {
+        self.solution.as_ref()
+    }
+
+    /// Reads commands until the user quits, with line editing and a history kept between sessions.
+    pub fn run(&mut self) -> Result<(), ReadlineError> {
+        let mut editor = DefaultEditor::new()?;
+        // there is no history the first time the shell is started
+        let _ = editor.load_history(HISTORY_FILE);
+        println!("rustic circuit shell, type help for a list of commands");
+        loop {
+            let line = match editor.readline("rustic> ") {
+                Ok(line) => line,
+                Err(ReadlineError::Interrupted | ReadlineError::Eof) => break,
+                Err(e) => return Err(e),
+            };
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            editor.add_history_entry(line)?;
+            if matches!(line, "quit" | "exit") {
+                break;
+            }
+            if let Err(e) = self.execute(line) {
+                println!("error: {e}");
+            }
+        }
+        editor.save_history(HISTORY_FILE)
+    }
+
+    /// Runs a single command, lines starting with '*' or '#' are comments.
+    pub fn execute(&mut self, line: &str) -> Result<(), CommandError> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let Some((&command, args)) = words.split_first() else {
+            return Ok(());
+        };
+        if command.starts_with('*') || command.starts_with('#') {
+            return Ok(());
+        }
+        let command = command.to_ascii_lowercase();
+        match command.as_str() {
+            "add" => self.add(args),
+            "connect" => self.connect(args),
+            "toggle" => self.toggle(args),
+            "solve" => self.solve(args),
+            "print" => self.print(args),
+            "thevenin" => self.thevenin(args),
+            "save" => self.save(args),
+            "load" => self.load(args),
+            "clear" => {
+                expect_args(args, 0, "clear")?;
+                *self = Self::new();
+                Ok(())
+            }
+            "help" => {
+                println!("{HELP}");
+                Ok(())
+            }
+            _ => Err(CommandError(format!(
+                "unknown command '{command}', type help for a list of commands"
+            ))),
+        }?;
+        if matches!(command.as_str(), "add" | "connect" | "toggle") {
+            self.script.push(words.join(" "));
+            // the last solution no longer belongs to the circuit
+            self.solution = None;
+        }
+        Ok(())
+    }
+
+    fn add(&mut self, args: &[&str]) -> Result<(), CommandError> {
+        let Some((&name, values)) = args.split_first() else {
+            return Err(CommandError("usage: add <name> [value]".to_string()));
+        };
+        if self.circuit.find_component(name).is_ok() {
+            return Err(CommandError(format!(
+                "there already is a component named {name}"
+            )));
+        }
+        let value = |what: &str| -> Result<f64, CommandError> {
+            let [text] = values else {
+                return Err(CommandError(format!("usage: add {name} <{what}>")));
+            };
+            netlist::parse_value(text)
+                .ok_or_else(|| CommandError(format!("'{text}' is not a valid {what}")))
+        };
+        let no_value = || -> Result<(), CommandError> {
+            if values.is_empty() {
+                Ok(())
+            } else {
+                Err(CommandError(format!("{name} does not take a value")))
+            }
+        };
+
+        let identifer = self.next_identifer(name);
+        let component = match name.chars().next().map(|c| c.to_ascii_uppercase()) {
+            Some('R') => ResistorComponent(Resistor::new(identifer, value("resistance")?)),
+            Some('C') => CapacitorComponent(Capacitor::new(identifer, value("capacitance")?)),
+            Some('L') => InductorComponent(Inductor::new(identifer, value("inductance")?)),
+            Some('V') => {
+                let source = DCVoltageSource::new(identifer, value("voltage")?);
+                DCVoltageSourceComponent(source)
+            }
+            Some('I') => {
+                let source = DCCurrentSource::new(identifer, value("current")?);
+                DCCurrentSourceComponent(source)
+            }
+            Some('D') => {
+                no_value()?;
+                DiodeComponent(Diode::new(identifer, 1e-14, 1.0, 0.0))
+            }
+            Some('S') => {
+                no_value()?;
+                SwitchSPDTComponent(SwitchSPDT::new(identifer))
+            }
+            Some('O') => {
+                no_value()?;
+                OpAmpComponent(OpAmp::new(identifer))
+            }
+            _ => {
+                return Err(CommandError(format!(
+                    "unknown component type '{name}', names start with R, C, L, D, V, I, S or O"
+                )))
+            }
+        };
+        self.circuit.add_component(component);
+        Ok(())
+    }
+
+    fn connect(&mut self, args: &[&str]) -> Result<(), CommandError> {
+        let [pin, node] = args else {
+            return Err(CommandError(
+                "usage: connect <name>.<pin> <node>".to_string(),
+            ));
+        };
+        let Some((component, pin)) = pin.split_once('.') else {
+            return Err(CommandError(format!(
+                "'{pin}' is not a pin, write it as <name>.<pin> like R1.anode"
+            )));
+        };
+        let pin = parse_pin(pin)?;
+        let comp_id = self.circuit.find_component(component)?;
+        if !self.circuit.components[comp_id]
+            .get_connection_types()
+            .contains(&pin)
+        {
+            let pins: Vec<&str> = self.circuit.components[comp_id]
+                .get_connection_types()
+                .iter()
+                .map(|p| pin_name(*p))
+                .collect();
+            return Err(CommandError(format!(
+                "{component} has no pin {}, its pins are {}",
+                pin_name(pin),
+                pins.join(", ")
+            )));
+        }
+        let node = self.node(node)?;
+        self.circuit.connect(component, pin, &node)?;
+        Ok(())
+    }
+
+    fn toggle(&mut self, args: &[&str]) -> Result<(), CommandError> {
+        let [name] = args else {
+            return Err(CommandError("usage: toggle <switch>".to_string()));
+        };
+        let comp_id = self.circuit.find_component(name)?;
+        match &mut self.circuit.components[comp_id] {
+            SwitchSPDTComponent(switch) => {
+                switch.toggle();
+                Ok(())
+            }
+            _ => Err(CommandError(format!("{name} is not a switch"))),
+        }
+    }
+
+    fn solve(&mut self, args: &[&str]) -> Result<(), CommandError> {
+        expect_args(args, 0, "solve")?;
+        let mut circuit = self.circuit.clone();
+        circuit.lock()?;
+        let mut solver = Solver::new(circuit);
+        let x = solver.solve_system()?;
+        let solution = Solution::new(&solver.circuit, x)?;
+        println!(
+            "solved {} nodes and {} components",
+            solver.nodes().len(),
+            solver.components().len()
+        );
+        self.solution = Some(solution);
+        Ok(())
+    }
+
+    fn thevenin(&self, args: &[&str]) -> Result<(), CommandError> {
+        let [positive, negative] = args else {
+            return Err(CommandError("usage: thevenin <node+> <node->".to_string()));
+        };
+        // the reference node is stored under the first ground name
+        let node = |name: &str| {
+            if GROUND_NAMES.iter().any(|g| name.eq_ignore_ascii_case(g)) {
+                GROUND_NAMES[0].to_string()
+            } else {
+                name.to_string()
+            }
+        };
+        let mut circuit = self.circuit.clone();
+        circuit.lock()?;
+        let equivalent = Solver::new(circuit).thevenin(&node(positive), &node(negative))?;
+        println!(
+            "Thevenin: {:.6}V in series with {:.6}Ohm",
+            equivalent.voltage, equivalent.resistance
+        );
+        match equivalent.norton_current() {
+            Some(current) => println!(
+                "Norton: {current:.6}A in parallel with {:.6}Ohm",
+                equivalent.resistance
+            ),
+            None => println!("Norton: none, the nodes are held at a fixed voltage"),
+        }
+        Ok(())
+    }
+
+    fn print(&self, args: &[&str]) -> Result<(), CommandError> {
+        let [what] = args else {
+            return Err(CommandError(
+                "usage: print <components|nodes|currents|power>".to_string(),
+            ));
+        };
+        if *what == "components" {
+            self.print_components();
+            return Ok(());
+        }
+        let solution = self
+            .solution
+            .as_ref()
+            .ok_or_else(|| CommandError("there is no solution, run solve first".to_string()))?;
+        let circuit = solution.circuit();
+        match *what {
+            "nodes" => {
+                for node in circuit.nodes.iter() {
+                    let name = node.get_name();
+                    println!("{name}: {:.6}V", solution.voltage(&name)?);
+                }
+            }
+            "currents" => {
+                for comp in circuit.components.iter() {
+                    let name = comp.get_name();
+                    for &pin in comp.get_connection_types() {
+                        let current = solution.current(&name, pin)?;
+                        println!("{name}.{}: {current:.6}A", pin_name(pin));
+                    }
+                }
+            }
+            "power" => {
+                let report = solution.power_report()?;
+                for (name, power) in report.components.iter() {
+                    println!("{name}: {power:.6}W");
+                }
+                println!(
+                    "sourced {:.6}W, dissipated {:.6}W",
+                    report.sourced, report.dissipated
+                );
+            }
+            _ => {
+                return Err(CommandError(format!(
+                    "cannot print '{what}', use components, nodes, currents or power"
+                )))
+            }
+        }
+        Ok(())
+    }
+
+    fn print_components(&self) {
+        for comp in self.circuit.components.iter() {
+            let pins: Vec<String> = comp
+                .get_connection_types()
+                .iter()
+                .map(|&pin| {
+                    let node = match comp.get_connection(pin).ok().and_then(|c| c.get_id()) {
+                        Some(node_id) => self.circuit.nodes[node_id].get_name(),
+                        None => "-".to_string(),
+                    };
+                    format!("{}={node}", pin_name(pin))
+                })
+                .collect();
+            println!("{} {}", comp.get_name(), pins.join(" "));
+        }
+    }
+
+    fn save(&self, args: &[&str]) -> Result<(), CommandError> {
+        let [path] = args else {
+            return Err(CommandError("usage: save <file>".to_string()));
+        };
+        let mut script = self.script.join("\n");
+        script.push('\n');
+        std::fs::write(path, script)?;
+        println!("saved {} commands to {path}", self.script.len());
+        Ok(())
+    }
+
+    fn load(&mut self, args: &[&str]) -> Result<(), CommandError> {
+        let [path] = args else {
+            return Err(CommandError("usage: load <file>".to_string()));
+        };
+        let input = std::fs::read_to_string(path)?;
+        // the current circuit is kept if the file has an error
+        let mut shell = Self::new();
+        for (number, line) in input.lines().enumerate() {
+            let line = line.trim();
+            let command = line
+                .split_whitespace()
+                .next()
+                .unwrap_or_default()
+                .to_ascii_lowercase();
+            if !matches!(command.as_str(), "" | "add" | "connect" | "toggle")
+                && !line.starts_with(['*', '#'])
+            {
+                return Err(CommandError(format!(
+                    "{path}:{}: only add, connect and toggle can be loaded",
+                    number + 1
+                )));
+            }
+            shell
+                .execute(line)
+                .map_err(|e| CommandError(format!("{path}:{}: {e}", number + 1)))?;
+        }
+        *self = shell;
+        println!(
+            "loaded {} components and {} nodes from {path}",
+            self.circuit.components.len(),
+            self.circuit.nodes.len()
+        );
+        Ok(())
+    }
+
+    fn next_identifer(&self, name: &str) -> Identifer {
+        let id = self.circuit.components.len();
+        Identifer::new(name.to_string(), id, name.to_string(), name.to_string())
+    }
+
+    /*
+     * The name the node is stored under, the reference node gets a ground component when it
+     * is first used.
+     */
+    fn node(&mut self, name: &str) -> Result<String, CommandError> {
+        if !GROUND_NAMES.iter().any(|g| name.eq_ignore_ascii_case(g)) {
+            return Ok(name.to_string());
+        }
+        let name = GROUND_NAMES[0];
+        if self.circuit.find_node(name).is_err() {
+            let node_id = self.circuit.add_named_node(name);
+            let ground = Ground::new(self.next_identifer(GROUND_COMPONENT));
+            let ground_id = ground.identifer.id;
+            self.circuit.add_component(GroundComponent(ground));
+            self.circuit
+                .connect_node(ground_id, node_id, GroundConnection)?;
+        }
+        Ok(name.to_string())
+    }
+}
+
+fn expect_args(args: &[&str], count: usize, usage: &str) -> Result<(), CommandError> {
+    if args.len() == count {
+        Ok(())
+    } else {
+        Err(CommandError(format!("usage: {usage}")))
+    }
+}
+
+fn parse_pin(name: &str) -> Result<ConnectionType, CommandError> {
+    let pin = match name.to_ascii_lowercase().as_str() {
+        "anode" => Anode,
+        "cathode" => Cathode,
+        "left" => Left,
+        "middle" => Middle,
+        "right" => Right,
+        "in+" | "noninverting" => NonInverting,
+        "in-" | "inverting" => Inverting,
+        "out" | "output" => Output,
+        "pin0" => Pin0,
+        "pin1" => Pin1,
+        "pin2" => Pin2,
+        "pin3" => Pin3,
+        _ => return Err(CommandError(format!("unknown pin '{name}'"))),
+    };
+    Ok(pin)
+}
+
+fn pin_name(pin: ConnectionType) -> &'static str {
+    match pin {
+        Anode => "anode",
+        Cathode => "cathode",
+        GroundConnection => "ground",
+        Left => "left",
+        Middle => "middle",
+        Right => "right",
+        NonInverting => "in+",
+        Inverting => "in-",
+        Output => "out",
+        Pin0 => "pin0",
+        Pin1 => "pin1",
+        Pin2 => "pin2",
+        Pin3 => "pin3",
+        _ => "pin",
+    }
+}
@@ -0,0 +1,153 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::circuit::Circuit;
+use crate::components::{Component, ConnectionType};
+use crate::error::CircuitError;
+
+/// The operating point of a solved circuit.
+/// potentials holds the potential of every node by node id and currents the currents of the
+/// components in the order of their current indices, the accessors look them up by name.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Solution {
+    circuit: Circuit,
+    pub potentials: Vec<f64>,
+    pub currents: Vec<f64>,
+    // the ids of the components and nodes by name, derived from the circuit
+    #[serde(skip)]
+    component_ids: HashMap<String, usize>,
+    #[serde(skip)]
+    node_ids: HashMap<String, usize>,
+}
+
+impl Solution {
+    /// Splits the solution vector of the circuit into the node potentials and the currents.
+    pub fn new(circuit: &Circuit, mut x: Vec<f64>) -> Result<Self, CircuitError> {
+        if !circuit.is_locked() {
+            return Err(CircuitError::NotLocked);
+        }
+        let currents = x.split_off(circuit.nodes.len());
+        let mut solution = Self {
+            circuit: circuit.clone(),
+            potentials: x,
+            currents,
+            component_ids: HashMap::new(),
+            node_ids: HashMap::new(),
+        };
+        solution.index_names();
+        Ok(solution)
+    }
+
+    // the first of several components or nodes with the same name is found, like in the circuit
+    fn index_names(&mut self) {
+        self.component_ids = HashMap::with_capacity(self.circuit.components.len());
+        for (comp_id, component) in self.circuit.components.iter().enumerate() {
+            self.component_ids
+                .entry(component.get_name())
+                .or_insert(comp_id);
+        }
+        self.node_ids = HashMap::with_capacity(self.circuit.nodes.len());
+        for (node_id, node) in self.circuit.nodes.iter().enumerate() {
+            self.node_ids
+                .entry(node.identifer.name.clone())
+                .or_insert(node_id);
+        }
+    }
+
+    /*
+     * Locks the circuit of a solution read from a file again and checks that the solution fits it.
+     */
+    pub(crate) fn restore_circuit(&mut self) -> Result<(), CircuitError> {
+        self.circuit.unlock();
+        self.circuit.lock()?;
+        let num_nodes = self.circuit.nodes.len();
+        if self.potentials.len() != num_nodes
+            || self.currents.len() != self.circuit.num_variables() - num_nodes
+        {
+            return Err(CircuitError::InvalidJson(
+                "the solution does not match its circuit".to_string(),
+            ));
+        }
+        self.index_names();
+        Ok(())
+    }
+
+    pub fn circuit(&self) -> &Circuit {
+        &self.circuit
+    }
+
+    /// The potential of the node with the given name.
+    pub fn voltage(&self, node: &str) -> Result<f64, CircuitError> {
+        let node_id = *self
+            .node_ids
+            .get(node)
+            .ok_or_else(|| CircuitError::UnknownNode(node.to_string()))?;
+        Ok(self.potentials[self.circuit.get_potential_index(node_id)])
+    }
+
+    /// The current unknowns of the component with the given name, a component can have more than one.
+    pub fn branch_currents(&self, component: &str) -> Result<&[f64], CircuitError> {
+        self.branch_currents_of(self.component(component)?)
+    }
+
+    /// The current flowing into the component through the given pin,
+    /// it is negative when the current flows out of the component into the node.
+    pub fn current(&self, component: &str, pin: ConnectionType) -> Result<f64, CircuitError> {
+        self.pin_current(self.component(component)?, pin)
+    }
+
+    pub(crate) fn pin_current(
+        &self,
+        component: &Component,
+        pin: ConnectionType,
+    ) -> Result<f64, CircuitError> {
+        let currents = self.branch_currents_of(component)?;
+        // the representative is the current the pin supplies to its node
+        let mut eq = vec![0.0; currents.len()];
+        component.current_representative(0, pin, &mut eq)?;
+        Ok(-eq.iter().zip(currents).map(|(c, i)| c * i).sum::<f64>())
+    }
+
+    /// The voltage from the first to the second pin of the component, V(anode) - V(cathode)
+    /// for two terminal components. Together with current(component, Anode) it follows the
+    /// passive sign convention.
+    pub fn voltage_across(&self, component: &str) -> Result<f64, CircuitError> {
+        let component = self.component(component)?;
+        let [first, second, ..] = component.get_connection_types() else {
+            return Err(CircuitError::InvalidParameter(format!(
+                "{} has no two pins to measure a voltage across",
+                component.get_name()
+            )));
+        };
+        Ok(self.pin_potential(component, *first)? - self.pin_potential(component, *second)?)
+    }
+
+    /// The potential of the node a pin of the component is connected to.
+    pub fn pin_potential(
+        &self,
+        component: &Component,
+        pin: ConnectionType,
+    ) -> Result<f64, CircuitError> {
+        let node_id = component.get_connection(pin)?.get_id().ok_or_else(|| {
+            CircuitError::DisconnectedPin {
+                component: component.get_name(),
+                pin,
+            }
+        })?;
+        Ok(self.potentials[self.circuit.get_potential_index(node_id)])
+    }
+
+    pub(crate) fn component(&self, name: &str) -> Result<&Component, CircuitError> {
+        let comp_id = *self
+            .component_ids
+            .get(name)
+            .ok_or_else(|| CircuitError::UnknownComponent(name.to_string()))?;
+        self.circuit.get_component(comp_id)
+    }
+
+    fn branch_currents_of(&self, component: &Component) -> Result<&[f64], CircuitError> {
+        let index = self.circuit.get_current_index(component)? - self.circuit.nodes.len();
+        Ok(&self.currents[index..index + component.get_currents()])
+    }
+}
@@ -1,36 +1,55 @@
 use crate::circuit;
 use crate::components::Component;
 use crate::components::Node;
+use crate::error::CircuitError;
+use crate::solution::Solution;
+use crate::sparse::{SparseLu, SparseMatrix};
 use circuit::Circuit;
 
-use nalgebra::LU;
-// use nalgebra::QR;
-use nalgebra::{DMatrix, DVector};
+/// The convergence criteria of the Newton-Raphson iteration used for nonlinear circuits.
+/// An iteration has converged when every unknown changed less than
+/// reltol * max(|x|, |x_prev|) + vntol for node potentials and + abstol for currents.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NewtonOptions {
+    pub reltol: f64,
+    pub abstol: f64,
+    pub vntol: f64,
+    pub max_iterations: usize,
+}
 
-/*
-* this struct contains the nesseday information to solve a circuit using the node voltage method.
-*/
+impl Default for NewtonOptions {
+    fn default() -> Self {
+        Self {
+            reltol: 1e-3,
+            abstol: 1e-12,
+            vntol: 1e-6,
+            max_iterations: 100,
+        }
+    }
+}
+
+/// this struct contains the nesseday information to solve a circuit using the node voltage method.
 #[derive(Debug, Clone)]
 pub struct Solver {
     pub circuit: Circuit,
-    pub is_solved: bool,
-    pub potentials: Vec<f64>,
-    pub currents: Vec<f64>,
+    /// the operating point found by the last call to solve
+    pub solution: Option<Solution>,
+    pub newton: NewtonOptions,
+    // the last factorized matrix with its factorization, reused while the matrix does not change
+    factorization: Option<(SparseMatrix<f64>, SparseLu<f64>)>,
 }
 
 /*
 * this impl block contains the methods to solve a circuit using the node voltage method.
 */
 impl Solver {
-    /*
-     * this method creates a new node voltage method struct.
-     */
+    /// this method creates a new node voltage method struct.
     pub fn new(circuit: Circuit) -> Self {
         Self {
             circuit,
-            is_solved: false,
-            potentials: Vec::new(),
-            currents: Vec::new(),
+            solution: None,
+            newton: NewtonOptions::default(),
+            factorization: None,
         }
     }
 
@@ -42,71 +61,192 @@ impl Solver {
         &self.circuit.components
     }
 
-    pub fn get_component(&self, id: usize) -> &Component {
-        &self.circuit.components[id]
+    pub fn get_component(&self, id: usize) -> Result<&Component, CircuitError> {
+        self.circuit.get_component(id)
     }
 
-    pub fn get_node(&self, id: usize) -> &Node {
-        &self.circuit.nodes[id]
+    pub fn get_node(&self, id: usize) -> Result<&Node, CircuitError> {
+        self.circuit
+            .nodes
+            .get(id)
+            .ok_or(CircuitError::NodeOutOfRange(id))
     }
 
     /*
-     * this method solves the circuit using the node voltage method.
+     * this method fills the sparse matrix M and the vector b of the system M * x = b
+     * every equation is stamped into a dense row, the columns it can refer to are moved into the
+     * matrix afterwards
      */
-    pub fn solve(&mut self) -> Result<(), String> {
-        let num_unknowns = self.circuit.num_variables;
+    pub(crate) fn build_system(&self) -> Result<(SparseMatrix<f64>, Vec<f64>), CircuitError> {
+        if !self.circuit.is_locked() {
+            return Err(CircuitError::NotLocked);
+        }
+        let num_unknowns = self.circuit.num_variables();
         let num_nodes = self.nodes().len();
         // fill matrix with equations for each node
         // M * x = b
 
-        let mut m = vec![0.0; num_unknowns.pow(2)];
+        let mut m = SparseMatrix::new(num_unknowns);
         let mut b = vec![0.0; num_unknowns];
+        let mut row = vec![0.0; num_unknowns];
 
         // KCL
-        for (node_id, row) in m.chunks_mut(num_unknowns).enumerate().take(num_nodes) {
-            self.circuit.currents_at_node_eq(node_id, row);
+        for node_id in 0..num_nodes {
+            self.circuit.currents_at_node_eq(node_id, &mut row)?;
+            m.take_columns(node_id, &mut row, &self.circuit.node_columns(node_id)?)?;
         }
 
         // Do the component-related equations
-        // TODO make this readable
-        self.components()
-            .iter()
-            .flat_map(|c| (0..c.get_currents()).map(move |eq_id| (c, eq_id)))
-            .enumerate()
-            .zip(m.chunks_mut(num_unknowns).skip(num_nodes))
-            .for_each(|((row_id, (c, eq_id)), row)| {
-                b[num_nodes + row_id] = c.equation(num_nodes + row_id - eq_id, row, eq_id);
-            });
-
-        for (r, bi) in m.chunks(num_unknowns).zip(&b) {
-            for v in r {
-                print!("{v:>6} ");
+        let mut row_id = num_nodes;
+        for c in self.components() {
+            let columns = c.stamp_columns(row_id);
+            for eq_id in 0..c.get_currents() {
+                if row_id >= num_unknowns {
+                    return Err(CircuitError::NotLocked);
+                }
+                b[row_id] = c.equation(row_id - eq_id, &mut row, eq_id)?;
+                m.take_columns(row_id, &mut row, &columns)?;
+                row_id += 1;
+            }
+        }
+
+        Ok((m, b))
+    }
+
+    /// The system M * x = b with M as dense rows, for nonlinear components it is stamped around
+    /// their last operating point. Only meant for printing small systems.
+    pub fn dense_system(&self) -> Result<(Vec<Vec<f64>>, Vec<f64>), CircuitError> {
+        let (m, b) = self.build_system()?;
+        Ok((m.to_dense_rows(), b))
+    }
+
+    /// this method builds and solves the system with the components in their current state
+    /// nonlinear components are re-linearized and the system is solved again until it converges
+    /// the result contains the node potentials followed by the component currents
+    pub fn solve_system(&mut self) -> Result<Vec<f64>, CircuitError> {
+        if !self.components().iter().any(|c| c.is_nonlinear()) {
+            let (m, b) = self.build_system()?;
+            return Ok(self.factorize(m)?.solve(&b));
+        }
+
+        let mut previous: Option<Vec<f64>> = None;
+        for _ in 0..self.newton.max_iterations {
+            let (m, b) = self.build_system()?;
+            let x = self.factorize(m)?.solve(&b);
+            let limited = self.update_operating_points(&x)?;
+            if !limited && previous.is_some_and(|p| self.converged(&p, &x)) {
+                return Ok(x);
+            }
+            previous = Some(x);
+        }
+        Err(CircuitError::NoConvergence(self.newton.max_iterations))
+    }
+
+    /*
+     * The LU factorization of m, when m is the same matrix as last time only the right hand side
+     * changed and the previous factorization is reused.
+     */
+    fn factorize(&mut self, m: SparseMatrix<f64>) -> Result<&SparseLu<f64>, CircuitError> {
+        let factorization = match self.factorization.take() {
+            Some((previous, lu)) if previous == m => (previous, lu),
+            _ => {
+                let lu = SparseLu::new(&m)?;
+                (m, lu)
             }
-            println!("   |    {bi}");
+        };
+        Ok(&self.factorization.insert(factorization).1)
+    }
+
+    fn update_operating_points(&mut self, x: &[f64]) -> Result<bool, CircuitError> {
+        let circuit = &mut self.circuit;
+        let mut limited = false;
+        for comp_id in 0..circuit.components.len() {
+            if !circuit.components[comp_id].is_nonlinear() {
+                continue;
+            }
+            let offset = circuit.get_current_index(&circuit.components[comp_id])?;
+            limited |= circuit.components[comp_id].update_operating_point(x, offset)?;
         }
-        let a = DMatrix::from_row_slice(num_unknowns, num_unknowns, &m);
-        let b = DVector::from_vec(b);
+        Ok(limited)
+    }
 
-        // solve the matrix
-        // let qr = QR::new(a);
-        let lu = LU::new(a);
+    fn converged(&self, previous: &[f64], x: &[f64]) -> bool {
+        let num_nodes = self.nodes().len();
+        let options = &self.newton;
+        previous.iter().zip(x).enumerate().all(|(i, (old, new))| {
+            let tolerance = if i < num_nodes {
+                options.vntol
+            } else {
+                options.abstol
+            };
+            (new - old).abs() <= options.reltol * new.abs().max(old.abs()) + tolerance
+        })
+    }
 
-        // let x = qr.solve(&b).expect("Failed to solve the linear system");
-        let x = lu.solve(&b).expect("Failed to solve the linear system");
+    /// this method solves the circuit using the node voltage method.
+    pub fn solve(&mut self) -> Result<&Solution, CircuitError> {
+        let x = self.solve_system()?;
 
         // set the potentials of the nodes
-        for mut node in self.circuit.nodes.iter_mut() {
+        for node in self.circuit.nodes.iter_mut() {
             node.potential = x[node.get_id()];
             node.locked = true;
         }
 
-        for i in 0..num_nodes {
-            self.potentials.push(x[i]);
-        }
-        for i in num_nodes..num_unknowns {
-            self.currents.push(x[i]);
-        }
-        self.is_solved = true;
-        return Ok(());
+        Ok(self.solution.insert(Solution::new(&self.circuit, x)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::netlist::parse;
+
+    fn divider(input: &str) -> Solver {
+        Solver::new(parse("test".to_string(), input).unwrap())
+    }
+
+    #[test]
+    fn the_factorization_is_reused_while_the_matrix_does_not_change() {
+        let mut solver = divider("V1 a 0 6\nR1 a b 1k\nR2 b 0 1k\n");
+        let (m, _) = solver.build_system().unwrap();
+        // cache the factorization of another matrix under this one, only a reuse gives its answer
+        let mut other = divider("V1 a 0 6\nR1 a b 1k\nR2 b 0 2k\n");
+        let (other_m, _) = other.build_system().unwrap();
+        let expected = other.solve_system().unwrap();
+        solver.factorization = Some((m.clone(), SparseLu::new(&other_m).unwrap()));
+
+        assert_eq!(solver.solve_system().unwrap(), expected);
+        assert!(solver
+            .factorization
+            .as_ref()
+            .is_some_and(|(cached, _)| *cached == m));
+    }
+
+    #[test]
+    fn the_factorization_is_rebuilt_when_the_matrix_changes() {
+        let mut solver = divider("V1 a 0 6\nR1 a b 1k\nR2 b 0 1k\n");
+        let b = solver.circuit.find_node("b").unwrap();
+        assert_eq!(solver.solve_system().unwrap()[b], 3.0);
+
+        // a new voltage only changes the right hand side
+        let v1 = solver.circuit.find_component("V1").unwrap();
+        solver.circuit.components[v1].set_sweep_value(12.0).unwrap();
+        let (cached, _) = solver.factorization.clone().unwrap();
+        assert_eq!(solver.solve_system().unwrap()[b], 6.0);
+        assert!(solver
+            .factorization
+            .as_ref()
+            .is_some_and(|(m, _)| *m == cached));
+
+        let r2 = solver.circuit.find_component("R2").unwrap();
+        solver.circuit.components[r2].set_sweep_value(2e3).unwrap();
+        let (m, _) = solver.build_system().unwrap();
+        assert_ne!(m, cached);
+        assert!((solver.solve_system().unwrap()[b] - 8.0).abs() < 1e-12);
+        assert!(solver
+            .factorization
+            .as_ref()
+            .is_some_and(|(cached, _)| *cached == m));
     }
 }
@@ -0,0 +1,430 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+use nalgebra::ComplexField;
+
+use crate::error::CircuitError;
+
+/*
+* The values a sparse matrix can hold, implemented for f64 and Complex<f64>.
+*/
+pub trait Scalar: ComplexField<RealField = f64> + Copy {}
+
+impl<T: ComplexField<RealField = f64> + Copy> Scalar for T {}
+
+// only pivots at least this fraction of the largest entry of their column are considered
+const PIVOT_THRESHOLD: f64 = 0.1;
+// entries below this fraction of the largest value that was added to them are treated as zero
+const SINGULAR_TOLERANCE: f64 = 8.0 * f64::EPSILON;
+// the number of sparsest columns searched for the pivot with the lowest markowitz cost
+const PIVOT_SEARCH_COLUMNS: usize = 4;
+
+/*
+* A square sparse matrix in triplet form, duplicate entries are summed.
+* The equations of the circuit are stamped into it row by row.
+*/
+#[derive(Debug, Clone, PartialEq)]
+pub struct SparseMatrix<T> {
+    size: usize,
+    triplets: Vec<(usize, usize, T)>,
+}
+
+/*
+* A square sparse matrix in compressed sparse column form.
+* The rows and values of column j are row_indices[col_ptr[j]..col_ptr[j + 1]] and the same range of values.
+*/
+#[derive(Debug, Clone)]
+pub struct CscMatrix<T> {
+    pub size: usize,
+    pub col_ptr: Vec<usize>,
+    pub row_indices: Vec<usize>,
+    pub values: Vec<T>,
+}
+
+impl<T: Scalar> SparseMatrix<T> {
+    pub fn new(size: usize) -> Self {
+        Self {
+            size,
+            triplets: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, row: usize, col: usize, value: T) {
+        if !value.is_zero() {
+            self.triplets.push((row, col, value));
+        }
+    }
+
+    /*
+     * Moves the non zero entries in the given columns of a dense row into the matrix and zeroes
+     * them, so the same row can be handed to the next equation. The rest of the row has to be zero,
+     * if it is not the whole row is cleared and an error names the first column outside of columns.
+     */
+    pub fn take_columns(
+        &mut self,
+        row: usize,
+        values: &mut [T],
+        columns: &[usize],
+    ) -> Result<(), CircuitError> {
+        for &col in columns {
+            let value = std::mem::replace(&mut values[col], T::zero());
+            if !value.is_zero() {
+                self.triplets.push((row, col, value));
+            }
+        }
+        match values.iter().position(|v| !v.is_zero()) {
+            Some(column) => {
+                values.fill(T::zero());
+                Err(CircuitError::InvalidStamp { row, column })
+            }
+            None => Ok(()),
+        }
+    }
+
+    /*
+     * Compresses the triplets into columns, duplicates are summed and the rows are sorted.
+     */
+    pub fn to_csc(&self) -> CscMatrix<T> {
+        let mut columns: Vec<Vec<(usize, T)>> = vec![Vec::new(); self.size];
+        for &(row, col, value) in &self.triplets {
+            columns[col].push((row, value));
+        }
+
+        let mut csc = CscMatrix {
+            size: self.size,
+            col_ptr: Vec::with_capacity(self.size + 1),
+            row_indices: Vec::with_capacity(self.triplets.len()),
+            values: Vec::with_capacity(self.triplets.len()),
+        };
+        csc.col_ptr.push(0);
+        for mut column in columns {
+            column.sort_by_key(|(row, _)| *row);
+            for (row, value) in column {
+                if csc.row_indices.len() > *csc.col_ptr.last().unwrap_or(&0)
+                    && csc.row_indices.last() == Some(&row)
+                {
+                    if let Some(last) = csc.values.last_mut() {
+                        *last += value;
+                    }
+                } else {
+                    csc.row_indices.push(row);
+                    csc.values.push(value);
+                }
+            }
+            csc.col_ptr.push(csc.row_indices.len());
+        }
+        csc
+    }
+
+    /*
+     * The matrix as dense rows, only meant for printing small systems.
+     */
+    pub fn to_dense_rows(&self) -> Vec<Vec<T>> {
+        let mut rows = vec![vec![T::zero(); self.size]; self.size];
+        for &(row, col, value) in &self.triplets {
+            rows[row][col] += value;
+        }
+        rows
+    }
+}
+
+// an entry of the matrix being factored and the largest magnitude that was added to it
+type Entry<T> = (T, f64);
+
+/*
+* The sparse LU factorization P A Q = L U of a square matrix.
+* The pivots are chosen with the markowitz criterion to limit fill in, with threshold pivoting
+* for numerical stability. Step k eliminates row row_order[k] and column col_order[k].
+*/
+#[derive(Debug, Clone)]
+pub struct SparseLu<T> {
+    size: usize,
+    row_order: Vec<usize>,
+    col_order: Vec<usize>,
+    // the multipliers of step k with the rows they were applied to
+    lower: Vec<Vec<(usize, T)>>,
+    // the pivot of step k followed by the rest of the pivot row with its columns
+    upper: Vec<(T, Vec<(usize, T)>)>,
+}
+
+impl<T: Scalar> SparseLu<T> {
+    /*
+     * Factors the matrix, returns SingularMatrix if there is a step without a usable pivot.
+     */
+    pub fn new(matrix: &SparseMatrix<T>) -> Result<Self, CircuitError> {
+        let csc = matrix.to_csc();
+        let n = csc.size;
+
+        // the active sub matrix, stored by row with the pattern of every column
+        let mut rows: Vec<HashMap<usize, Entry<T>>> = vec![HashMap::new(); n];
+        let mut cols: Vec<HashSet<usize>> = vec![HashSet::new(); n];
+        for (col, range) in csc.col_ptr.windows(2).enumerate() {
+            for k in range[0]..range[1] {
+                let (row, value) = (csc.row_indices[k], csc.values[k]);
+                if !value.is_zero() {
+                    rows[row].insert(col, (value, value.modulus()));
+                    cols[col].insert(row);
+                }
+            }
+        }
+
+        let mut lu = Self {
+            size: n,
+            row_order: Vec::with_capacity(n),
+            col_order: Vec::with_capacity(n),
+            lower: Vec::with_capacity(n),
+            upper: Vec::with_capacity(n),
+        };
+        // the active columns by their number of entries, entries that are out of date are skipped
+        let mut sparsest: BinaryHeap<Reverse<(usize, usize)>> = cols
+            .iter()
+            .enumerate()
+            .map(|(col, rows)| Reverse((rows.len(), col)))
+            .collect();
+        let mut eliminated = vec![false; n];
+
+        for _ in 0..n {
+            let (pivot_row, pivot_col) =
+                Self::choose_pivot(&rows, &cols, &mut sparsest, &eliminated)
+                    .ok_or(CircuitError::SingularMatrix)?;
+            eliminated[pivot_col] = true;
+
+            let mut upper_row = rows[pivot_row].clone();
+            rows[pivot_row].clear();
+            let (pivot, _) = upper_row
+                .remove(&pivot_col)
+                .ok_or(CircuitError::SingularMatrix)?;
+            for col in upper_row.keys() {
+                cols[*col].remove(&pivot_row);
+            }
+
+            // eliminate the pivot column from every other row
+            let mut multipliers = Vec::new();
+            for row in std::mem::take(&mut cols[pivot_col]) {
+                if row == pivot_row {
+                    continue;
+                }
+                let Some((value, _)) = rows[row].remove(&pivot_col) else {
+                    continue;
+                };
+                let l = value / pivot;
+                for (&col, &(u, _)) in upper_row.iter() {
+                    let update = l * u;
+                    let entry = rows[row].entry(col).or_insert((T::zero(), 0.0));
+                    entry.0 -= update;
+                    entry.1 = entry.1.max(update.modulus());
+                    cols[col].insert(row);
+                }
+                multipliers.push((row, l));
+            }
+            for col in upper_row.keys() {
+                sparsest.push(Reverse((cols[*col].len(), *col)));
+            }
+            let mut upper_row: Vec<(usize, T)> = upper_row
+                .into_iter()
+                .map(|(col, (value, _))| (col, value))
+                .collect();
+            upper_row.sort_by_key(|(col, _)| *col);
+
+            lu.row_order.push(pivot_row);
+            lu.col_order.push(pivot_col);
+            lu.lower.push(multipliers);
+            lu.upper.push((pivot, upper_row));
+        }
+        Ok(lu)
+    }
+
+    /*
+     * Searches the sparsest active columns for the entry with the lowest markowitz cost
+     * (r - 1)(c - 1) whose magnitude is at least PIVOT_THRESHOLD of the largest in its column.
+     * There is no pivot if the sparsest columns are (numerically) empty, the matrix is singular then.
+     * An entry that is only a rounding error of the values that cancelled in it counts as zero,
+     * so badly scaled but regular matrices keep their pivots.
+     */
+    fn choose_pivot(
+        rows: &[HashMap<usize, Entry<T>>],
+        cols: &[HashSet<usize>],
+        sparsest: &mut BinaryHeap<Reverse<(usize, usize)>>,
+        eliminated: &[bool],
+    ) -> Option<(usize, usize)> {
+        let magnitude = |row: usize, col: usize| {
+            let (value, largest) = rows[row][&col];
+            if value.modulus() <= SINGULAR_TOLERANCE * largest {
+                0.0
+            } else {
+                value.modulus()
+            }
+        };
+        let mut searched = Vec::with_capacity(PIVOT_SEARCH_COLUMNS);
+        while searched.len() < PIVOT_SEARCH_COLUMNS {
+            let Some(Reverse((count, col))) = sparsest.pop() else {
+                break;
+            };
+            if !eliminated[col] && cols[col].len() == count && !searched.contains(&col) {
+                searched.push(col);
+            }
+        }
+
+        let mut best: Option<(usize, f64, usize, usize)> = None;
+        for &col in searched.iter() {
+            let column_max = cols[col]
+                .iter()
+                .map(|&row| magnitude(row, col))
+                .fold(0.0, f64::max);
+            if column_max == 0.0 {
+                continue;
+            }
+            for &row in cols[col].iter() {
+                let magnitude = magnitude(row, col);
+                if magnitude < PIVOT_THRESHOLD * column_max {
+                    continue;
+                }
+                let cost = (rows[row].len() - 1) * (cols[col].len() - 1);
+                let better = match best {
+                    None => true,
+                    Some((best_cost, best_magnitude, best_row, best_col)) => {
+                        (cost, -magnitude, row, col)
+                            < (best_cost, -best_magnitude, best_row, best_col)
+                    }
+                };
+                if better {
+                    best = Some((cost, magnitude, row, col));
+                }
+            }
+        }
+        // the columns that were not chosen stay candidates for the next step
+        for col in searched {
+            if best.map(|(_, _, _, best_col)| best_col) != Some(col) {
+                sparsest.push(Reverse((cols[col].len(), col)));
+            }
+        }
+        best.map(|(_, _, row, col)| (row, col))
+    }
+
+    /*
+     * Solves A x = b with the factorization, it can be reused for any number of right hand sides.
+     */
+    pub fn solve(&self, b: &[T]) -> Vec<T> {
+        // forward substitution, L y = P b
+        let mut b = b.to_vec();
+        let mut y = Vec::with_capacity(self.size);
+        for (k, multipliers) in self.lower.iter().enumerate() {
+            let y_k = b[self.row_order[k]];
+            for &(row, l) in multipliers {
+                b[row] -= l * y_k;
+            }
+            y.push(y_k);
+        }
+
+        // back substitution, U Q^T x = y
+        let mut x = vec![T::zero(); self.size];
+        for k in (0..self.size).rev() {
+            let (pivot, ref row) = self.upper[k];
+            let mut sum = y[k];
+            for &(col, u) in row {
+                sum -= u * x[col];
+            }
+            x[self.col_order[k]] = sum / pivot;
+        }
+        x
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::Complex;
+
+    use super::*;
+
+    fn matrix<T: Scalar>(rows: &[&[T]]) -> SparseMatrix<T> {
+        let mut m = SparseMatrix::new(rows.len());
+        for (i, row) in rows.iter().enumerate() {
+            for (j, value) in row.iter().enumerate() {
+                m.push(i, j, *value);
+            }
+        }
+        m
+    }
+
+    fn assert_solves(rows: &[&[f64]], x: &[f64]) {
+        let b: Vec<f64> = rows
+            .iter()
+            .map(|row| row.iter().zip(x).map(|(a, x)| a * x).sum())
+            .collect();
+        let solved = SparseLu::new(&matrix(rows)).unwrap().solve(&b);
+        for (s, x) in solved.iter().zip(x) {
+            assert!((s - x).abs() <= 1e-12 * x.abs().max(1.0), "{solved:?}");
+        }
+    }
+
+    #[test]
+    fn zero_diagonal_needs_pivoting() {
+        assert_solves(&[&[0.0, 1.0], &[1.0, 0.0]], &[3.0, 2.0]);
+        // the shape of a voltage source between a node and ground
+        assert_solves(
+            &[&[1e-3, 0.0, 1.0], &[0.0, 2e-3, -1.0], &[1.0, -1.0, 0.0]],
+            &[5.0, -1.0, 2e-3],
+        );
+    }
+
+    #[test]
+    fn small_pivots_are_passed_over() {
+        // eliminating with 1e-20 first would swamp the 1 below it
+        assert_solves(&[&[1e-20, 1.0], &[1.0, 1.0]], &[1.0, 1.0]);
+    }
+
+    #[test]
+    fn badly_scaled_regular_matrices_factor() {
+        assert_solves(&[&[1e-13, 1.0], &[1.0, 0.0]], &[2.0, 7.0]);
+        assert_solves(
+            &[&[1e13, 1.0, 0.0], &[1.0, 1e-13, 1.0], &[0.0, 1.0, 1.0]],
+            &[1.0, 2.0, 3.0],
+        );
+    }
+
+    #[test]
+    fn empty_columns_are_singular() {
+        let m = matrix(&[&[1.0, 0.0], &[2.0, 0.0]]);
+        assert_eq!(SparseLu::new(&m).unwrap_err(), CircuitError::SingularMatrix);
+    }
+
+    #[test]
+    fn entries_that_cancel_are_singular() {
+        // the second row is exactly three times the first, elimination leaves only a rounding error
+        let m = matrix(&[&[0.1, 0.3], &[0.3, 0.9]]);
+        assert_eq!(SparseLu::new(&m).unwrap_err(), CircuitError::SingularMatrix);
+        let m = matrix(&[&[1.0, 2.0, 3.0], &[4.0, 5.0, 6.0], &[7.0, 8.0, 9.0]]);
+        assert_eq!(SparseLu::new(&m).unwrap_err(), CircuitError::SingularMatrix);
+    }
+
+    #[test]
+    fn duplicate_entries_are_summed() {
+        let mut m = matrix(&[&[1.0, 0.0], &[0.0, 1.0]]);
+        m.push(0, 0, 1.0);
+        assert_eq!(m.to_dense_rows(), [[2.0, 0.0], [0.0, 1.0]]);
+        assert_eq!(SparseLu::new(&m).unwrap().solve(&[4.0, 3.0]), [2.0, 3.0]);
+    }
+
+    #[test]
+    fn complex_systems() {
+        let j = Complex::new(0.0, 1.0);
+        let one = Complex::new(1.0, 0.0);
+        let m = matrix(&[&[one, j], &[j, one]]);
+        // the determinant is 1 - j * j = 2
+        let x = SparseLu::new(&m).unwrap().solve(&[one + j, j + one]);
+        for (x, expected) in x.iter().zip([one, one]) {
+            assert!((x - expected).modulus() < 1e-12, "{x}");
+        }
+    }
+
+    #[test]
+    fn stray_entries_clear_the_row() {
+        let mut m = SparseMatrix::new(3);
+        let mut row = vec![1.0, 2.0, 3.0];
+        assert_eq!(
+            m.take_columns(0, &mut row, &[0, 1]),
+            Err(CircuitError::InvalidStamp { row: 0, column: 2 })
+        );
+        assert_eq!(row, [0.0, 0.0, 0.0]);
+    }
+}
@@ -0,0 +1,379 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+
+use crate::circuit::Circuit;
+use crate::components::{BranchKind, Component, Connected, ConnectionType, Disconnected};
+
+/// A problem with the topology of a circuit that makes it impossible to solve.
+/// Every issue names the components (or nodes) that cause it.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TopologyIssue {
+    UnconnectedPin {
+        component: String,
+        pin: ConnectionType,
+    },
+    EmptyNode {
+        node: String,
+    },
+    // a current controlled component refers to a component that does not exist
+    UnknownControl {
+        component: String,
+        control: String,
+    },
+    SingleConnectionNode {
+        node: String,
+        component: String,
+    },
+    // a part of the circuit that is not connected to ground at all
+    NoGroundReference {
+        components: Vec<String>,
+    },
+    // a loop made only of voltage sources, inductors, switches and grounds
+    VoltageSourceLoop {
+        components: Vec<String>,
+    },
+    // a part of the circuit that is only connected to ground through current sources or capacitors
+    CurrentSourceCutSet {
+        components: Vec<String>,
+    },
+}
+
+impl TopologyIssue {
+    /// The names of the components that cause the issue, empty for an empty node.
+    pub fn components(&self) -> Vec<&str> {
+        match self {
+            TopologyIssue::UnconnectedPin { component, .. }
+            | TopologyIssue::UnknownControl { component, .. }
+            | TopologyIssue::SingleConnectionNode { component, .. } => vec![component],
+            TopologyIssue::EmptyNode { .. } => Vec::new(),
+            TopologyIssue::NoGroundReference { components }
+            | TopologyIssue::VoltageSourceLoop { components }
+            | TopologyIssue::CurrentSourceCutSet { components } => {
+                components.iter().map(String::as_str).collect()
+            }
+        }
+    }
+}
+
+impl fmt::Display for TopologyIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TopologyIssue::UnconnectedPin { component, pin } => {
+                write!(f, "pin {pin:?} of {component} is not connected")
+            }
+            TopologyIssue::EmptyNode { node } => write!(f, "node {node} has no connections"),
+            TopologyIssue::UnknownControl { component, control } => {
+                write!(
+                    f,
+                    "{component} is controlled by {control} which does not exist"
+                )
+            }
+            TopologyIssue::SingleConnectionNode { node, component } => {
+                write!(f, "node {node} is only connected to {component}")
+            }
+            TopologyIssue::NoGroundReference { components } => {
+                write!(f, "{} have no connection to ground", components.join(", "))
+            }
+            TopologyIssue::VoltageSourceLoop { components } => write!(
+                f,
+                "{} form a loop of voltage sources",
+                components.join(", ")
+            ),
+            TopologyIssue::CurrentSourceCutSet { components } => write!(
+                f,
+                "part of the circuit is only connected to ground through {}",
+                components.join(", ")
+            ),
+        }
+    }
+}
+
+/*
+* Union find over the nodes of a circuit, the extra last element is the reference (ground) node.
+*/
+struct DisjointSet {
+    parent: Vec<usize>,
+}
+
+impl DisjointSet {
+    fn new(size: usize) -> Self {
+        Self {
+            parent: (0..size).collect(),
+        }
+    }
+
+    fn find(&mut self, mut i: usize) -> usize {
+        while self.parent[i] != i {
+            self.parent[i] = self.parent[self.parent[i]];
+            i = self.parent[i];
+        }
+        i
+    }
+
+    // returns false if a and b already were in the same set
+    fn union(&mut self, a: usize, b: usize) -> bool {
+        let (a, b) = (self.find(a), self.find(b));
+        self.parent[a] = b;
+        a != b
+    }
+}
+
+/*
+* A branch of a component with the nodes at both of its ends.
+*/
+struct NodeBranch {
+    component: usize,
+    kind: BranchKind,
+    from: usize,
+    to: usize,
+}
+
+impl Circuit {
+    /// Checks that the circuit can be solved, the returned list is empty if it can.
+    pub fn validate(&self) -> Vec<TopologyIssue> {
+        let mut issues = Vec::new();
+        let reference = self.nodes.len();
+
+        // unconnected pins
+        let mut branches = Vec::new();
+        for (comp_id, component) in self.components.iter().enumerate() {
+            for pin in component.get_connection_types() {
+                if let Ok(Disconnected(_)) = component.get_connection(*pin) {
+                    issues.push(TopologyIssue::UnconnectedPin {
+                        component: component.get_name(),
+                        pin: *pin,
+                    });
+                }
+            }
+            if let Some(control) = component.control_component() {
+                if self.find_component(control).is_err() {
+                    issues.push(TopologyIssue::UnknownControl {
+                        component: component.get_name(),
+                        control: control.to_string(),
+                    });
+                }
+            }
+            branches.extend(self.node_branches(comp_id, component, reference));
+        }
+
+        // nodes with less than two connections, a pin connected twice to the same node counts once
+        let mut on_branch = vec![false; reference + 1];
+        for branch in branches.iter() {
+            on_branch[branch.from] = true;
+            on_branch[branch.to] = true;
+        }
+        for (node_id, node) in self.nodes.iter().enumerate() {
+            let mut pins: Vec<(usize, ConnectionType)> = Vec::new();
+            for connection in node.connections.iter() {
+                if let Connected(comp_id, pin) = *connection {
+                    if !pins.contains(&(comp_id, pin)) {
+                        pins.push((comp_id, pin));
+                    }
+                }
+            }
+            match pins.as_slice() {
+                [] => issues.push(TopologyIssue::EmptyNode {
+                    node: node.get_name(),
+                }),
+                [(comp_id, _)] => issues.push(TopologyIssue::SingleConnectionNode {
+                    node: node.get_name(),
+                    component: self.component_name(Some(*comp_id)),
+                }),
+                // only sensed by control or input pins, nothing sets the potential of the node
+                _ if !on_branch[node_id] => {
+                    let mut components = Vec::new();
+                    for (comp_id, _) in pins {
+                        let name = self.component_name(Some(comp_id));
+                        if !components.contains(&name) {
+                            components.push(name);
+                        }
+                    }
+                    issues.push(TopologyIssue::NoGroundReference { components });
+                }
+                _ => {}
+            }
+        }
+
+        issues.extend(self.voltage_source_loops(&branches, reference));
+        issues.extend(self.floating_subcircuits(&branches, reference));
+        issues
+    }
+
+    /// Checks if two nodes are joined by a path of voltage defined branches, the voltage between
+    /// them is then fixed whatever current flows between them.
+    pub(crate) fn voltage_defined_path(&self, a: usize, b: usize) -> bool {
+        let reference = self.nodes.len();
+        let mut sets = DisjointSet::new(reference + 1);
+        for (comp_id, component) in self.components.iter().enumerate() {
+            for branch in self.node_branches(comp_id, component, reference) {
+                if branch.kind == BranchKind::VoltageDefined {
+                    sets.union(branch.from, branch.to);
+                }
+            }
+        }
+        sets.find(a) == sets.find(b)
+    }
+
+    // the branches of a component whose pins are connected
+    fn node_branches(
+        &self,
+        comp_id: usize,
+        component: &Component,
+        reference: usize,
+    ) -> Vec<NodeBranch> {
+        let node_of = |pin| component.get_connection(pin).ok()?.get_id();
+        component
+            .get_branches()
+            .into_iter()
+            .filter_map(|branch| {
+                let from = match branch.from {
+                    Some(pin) => node_of(pin),
+                    None => Some(reference),
+                };
+                Some(NodeBranch {
+                    component: comp_id,
+                    kind: branch.kind,
+                    from: from?,
+                    to: node_of(branch.to)?,
+                })
+            })
+            .collect()
+    }
+
+    fn component_name(&self, comp_id: Option<usize>) -> String {
+        comp_id
+            .and_then(|id| self.components.get(id))
+            .map(|c| c.get_name())
+            .unwrap_or_default()
+    }
+
+    /*
+     * Finds loops made only of voltage defined branches by building a spanning forest of them,
+     * every branch that closes a loop in the forest is reported with the path it closes.
+     */
+    fn voltage_source_loops(
+        &self,
+        branches: &[NodeBranch],
+        reference: usize,
+    ) -> Vec<TopologyIssue> {
+        let mut issues = Vec::new();
+        let mut sets = DisjointSet::new(reference + 1);
+        let mut forest: HashMap<usize, Vec<(usize, usize)>> = HashMap::new();
+
+        for branch in branches
+            .iter()
+            .filter(|b| b.kind == BranchKind::VoltageDefined)
+        {
+            if sets.union(branch.from, branch.to) {
+                forest
+                    .entry(branch.from)
+                    .or_default()
+                    .push((branch.to, branch.component));
+                forest
+                    .entry(branch.to)
+                    .or_default()
+                    .push((branch.from, branch.component));
+                continue;
+            }
+            let mut components: Vec<String> = Self::forest_path(&forest, branch.from, branch.to)
+                .into_iter()
+                .map(|id| self.component_name(Some(id)))
+                .collect();
+            components.push(self.component_name(Some(branch.component)));
+            issues.push(TopologyIssue::VoltageSourceLoop { components });
+        }
+        issues
+    }
+
+    // the components on the path between two nodes of the forest
+    fn forest_path(
+        forest: &HashMap<usize, Vec<(usize, usize)>>,
+        from: usize,
+        to: usize,
+    ) -> Vec<usize> {
+        let mut previous: HashMap<usize, (usize, usize)> = HashMap::new();
+        let mut queue = VecDeque::from([from]);
+        while let Some(node) = queue.pop_front() {
+            if node == to {
+                break;
+            }
+            for (next, component) in forest.get(&node).into_iter().flatten() {
+                if *next != from && !previous.contains_key(next) {
+                    previous.insert(*next, (node, *component));
+                    queue.push_back(*next);
+                }
+            }
+        }
+
+        let mut path = Vec::new();
+        let mut node = to;
+        while let Some((prev, component)) = previous.get(&node) {
+            path.push(*component);
+            node = *prev;
+        }
+        path.reverse();
+        path
+    }
+
+    /*
+     * Finds parts of the circuit without a path to ground. Parts that are not connected to ground
+     * at all are reported with all of their components, parts that are only connected through
+     * current defined branches are reported with the branches that cut them off.
+     */
+    fn floating_subcircuits(
+        &self,
+        branches: &[NodeBranch],
+        reference: usize,
+    ) -> Vec<TopologyIssue> {
+        let mut issues = Vec::new();
+        let mut all = DisjointSet::new(reference + 1);
+        let mut dc = DisjointSet::new(reference + 1);
+        for branch in branches {
+            all.union(branch.from, branch.to);
+            if branch.kind != BranchKind::CurrentDefined {
+                dc.union(branch.from, branch.to);
+            }
+        }
+
+        // group the components by the part of the circuit they are in
+        let mut floating: Vec<(usize, Vec<String>)> = Vec::new();
+        let mut cut_sets: Vec<(usize, Vec<String>)> = Vec::new();
+        let grounded = all.find(reference);
+        let dc_grounded = dc.find(reference);
+        for branch in branches {
+            let name = self.component_name(Some(branch.component));
+            if all.find(branch.from) != grounded {
+                Self::add_to_group(&mut floating, all.find(branch.from), name);
+            } else if branch.kind == BranchKind::CurrentDefined {
+                // the branch cuts off every side of it that has no other path to ground
+                let (from, to) = (dc.find(branch.from), dc.find(branch.to));
+                if from != dc_grounded {
+                    Self::add_to_group(&mut cut_sets, from, name.clone());
+                }
+                if to != dc_grounded {
+                    Self::add_to_group(&mut cut_sets, to, name);
+                }
+            }
+        }
+
+        issues.extend(
+            floating
+                .into_iter()
+                .map(|(_, components)| TopologyIssue::NoGroundReference { components }),
+        );
+        issues.extend(
+            cut_sets
+                .into_iter()
+                .map(|(_, components)| TopologyIssue::CurrentSourceCutSet { components }),
+        );
+        issues
+    }
+
+    fn add_to_group(groups: &mut Vec<(usize, Vec<String>)>, group: usize, name: String) {
+        match groups.iter_mut().find(|(g, _)| *g == group) {
+            Some((_, names)) if !names.contains(&name) => names.push(name),
+            Some(_) => {}
+            None => groups.push((group, vec![name])),
+        }
+    }
+}
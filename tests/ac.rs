@@ -0,0 +1,31 @@
+use rustic::analysis::AcSweep;
+use rustic::netlist::parse;
+use rustic::{CircuitError, Solver};
+
+const RC: &str = "V1 in 0 DC 0 AC 1\nR1 in out 1k\nC1 out 0 1u\n";
+
+#[test]
+fn logarithmic_sweeps_have_to_start_above_zero() {
+    let mut solver = Solver::new(parse("test".to_string(), RC).unwrap());
+    for sweep in [AcSweep::Decade(10), AcSweep::Octave(10)] {
+        for f_start in [0.0, -10.0] {
+            assert!(matches!(
+                solver.ac(sweep, f_start, 1e3),
+                Err(CircuitError::InvalidParameter(_))
+            ));
+        }
+    }
+    // a linear sweep can start at DC
+    let result = solver.ac(AcSweep::Linear(3), 0.0, 1e3).unwrap();
+    assert_eq!(result.frequencies, vec![0.0, 500.0, 1e3]);
+}
+
+#[test]
+fn rc_low_pass_corner() {
+    let mut solver = Solver::new(parse("test".to_string(), RC).unwrap());
+    let corner = 1.0 / (2.0 * std::f64::consts::PI * 1e3 * 1e-6);
+    let result = solver.ac(AcSweep::Linear(1), corner, corner).unwrap();
+    let out = solver.circuit.find_node("out").unwrap();
+    assert!((result.magnitude(0, out) - 0.5f64.sqrt()).abs() < 1e-9);
+    assert!((result.phase(0, out) + 45.0).abs() < 1e-9);
+}
@@ -0,0 +1,55 @@
+use rustic::components::{ComponentTrait, ConnectionType::Anode, Identifer, Resistor};
+use rustic::netlist::parse;
+use rustic::{CircuitError, Solver};
+
+#[test]
+fn a_locked_circuit_cannot_be_connected() {
+    let mut circuit = parse("test".to_string(), "V1 a 0 1\nR1 a 0 1k\n").unwrap();
+    assert!(circuit.is_locked());
+    let r1 = circuit.find_component("R1").unwrap();
+    assert_eq!(
+        circuit.connect_node(r1, 0, Anode),
+        Err(CircuitError::AlreadyLocked)
+    );
+    assert_eq!(
+        circuit.connect("R1", Anode, "b"),
+        Err(CircuitError::AlreadyLocked)
+    );
+
+    circuit.unlock();
+    circuit.connect_node(r1, 0, Anode).unwrap();
+}
+
+#[test]
+fn out_of_range_ids_are_errors() {
+    let circuit = parse("test".to_string(), "V1 a 0 1\nR1 a 0 1k\n").unwrap();
+    assert_eq!(
+        circuit.get_potential(7),
+        Err(CircuitError::NodeOutOfRange(7))
+    );
+
+    let solver = Solver::new(circuit);
+    assert_eq!(
+        solver.get_component(9).unwrap_err(),
+        CircuitError::ComponentOutOfRange(9)
+    );
+    assert_eq!(
+        solver.get_node(7).unwrap_err(),
+        CircuitError::NodeOutOfRange(7)
+    );
+    assert!(solver.get_node(0).is_ok());
+}
+
+#[test]
+fn components_reject_equations_they_do_not_have() {
+    let name = "R1".to_string();
+    let resistor = Resistor::new(Identifer::new(name.clone(), 0, name.clone(), name), 1e3);
+    let mut row = vec![0.0; 4];
+    assert_eq!(
+        resistor.equation(2, &mut row, 1),
+        Err(CircuitError::InvalidEquation {
+            component: "R1".to_string(),
+            eq_id: 1
+        })
+    );
+}
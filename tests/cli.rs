@@ -0,0 +1,163 @@
+use std::path::PathBuf;
+
+use rustic::analysis::{DcSweep, TheveninEquivalent};
+use rustic::cli;
+use rustic::components::IntegrationMethod;
+use rustic::netlist::parse;
+use rustic::{Solution, Solver};
+
+fn solver(input: &str) -> Solver {
+    Solver::new(parse("test".to_string(), input).expect("the netlist parses"))
+}
+
+/*
+* A file in the temporary directory that is removed when it goes out of scope.
+*/
+struct TempFile(PathBuf);
+
+impl TempFile {
+    fn new(name: &str, contents: &str) -> Self {
+        let file = format!("rustic-cli-{}-{name}", std::process::id());
+        let path = std::env::temp_dir().join(file);
+        std::fs::write(&path, contents).unwrap();
+        Self(path)
+    }
+
+    fn path(&self) -> &str {
+        self.0.to_str().unwrap()
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+const DIVIDER: &str = "V1 in 0 10\nR1 in mid 1k\nR2 mid 0 1k\n";
+
+#[test]
+fn the_demo_circuit_solves() {
+    // each switch is toggled to the right, V1 drives R2 and V2 drives R4
+    let mut solver = Solver::new(cli::demo_circuit().unwrap());
+    let solution = solver.solve().unwrap();
+    assert!((solution.voltage("s1_right").unwrap() - 3.0).abs() < 1e-9);
+    assert!((solution.voltage("v2").unwrap() + 3.0).abs() < 1e-9);
+    assert!((solution.branch_currents("R2").unwrap()[0] - 1.5e-3).abs() < 1e-12);
+    assert!((solution.branch_currents("R4").unwrap()[0] - 6e-5).abs() < 1e-12);
+    assert!(solution.power_report().unwrap().is_balanced());
+}
+
+#[test]
+fn read_a_netlist() {
+    let file = TempFile::new("divider.cir", DIVIDER);
+    let circuit = cli::read_circuit(file.path()).unwrap();
+    assert!(circuit.find_component("R2").is_ok());
+}
+
+#[test]
+fn read_a_circuit_document() {
+    let document = rustic::json::to_json(&solver(DIVIDER).circuit).unwrap();
+    let file = TempFile::new("divider.json", &document);
+    let circuit = cli::read_circuit(file.path()).unwrap();
+    assert!(circuit.find_node("mid").is_ok());
+}
+
+#[test]
+fn read_errors_name_the_file() {
+    let missing = TempFile::new("missing.cir", "");
+    std::fs::remove_file(&missing.0).unwrap();
+    let message = cli::read_circuit(missing.path()).unwrap_err();
+    assert!(message.starts_with(&format!("Failed to read {}: ", missing.path())));
+
+    let file = TempFile::new("bad.cir", "V1 in 0 10\nR1 in\n");
+    let message = cli::read_circuit(file.path()).unwrap_err();
+    assert!(
+        message.starts_with(&format!("{}:2", file.path())),
+        "{message}"
+    );
+
+    let file = TempFile::new("bad.json", "{");
+    let message = cli::read_circuit(file.path()).unwrap_err();
+    assert!(message.starts_with(&format!("Failed to load {}: ", file.path())));
+}
+
+#[test]
+fn format_a_solution_and_its_power() {
+    let mut solver = solver(DIVIDER);
+    let solution = solver.solve().unwrap().clone();
+    let report = cli::format_solution(&solution).unwrap();
+    assert!(report.contains("Component: R1.0: 0.005000A\n"), "{report}");
+    assert!(report.contains("Node: mid: 5.00V\n"), "{report}");
+
+    let power = cli::format_power(&solution.power_report().unwrap());
+    assert!(power.contains("Power: R2: 0.025000W\n"), "{power}");
+    assert!(power.ends_with("Dissipated: 0.050000W Balance: 0.000e0W (conserved)\n"));
+}
+
+#[test]
+fn format_the_system() {
+    let system = cli::format_system(&solver(DIVIDER)).unwrap();
+    let rows: Vec<&str> = system.lines().collect();
+    // in, mid and 0, and a current for each component and the ground the netlist adds
+    assert_eq!(rows.len(), 7);
+    assert!(rows.iter().all(|row| row.contains("   |    ")));
+}
+
+#[test]
+fn format_thevenin() {
+    let equivalent = TheveninEquivalent {
+        positive: "a".to_string(),
+        negative: "0".to_string(),
+        voltage: 2.0,
+        resistance: 500.0,
+    };
+    assert_eq!(
+        cli::format_thevenin(&equivalent),
+        "Thevenin: a-0: 2.000000V in series with 500.000000Ohm\n\
+         Norton: a-0: 0.004000A in parallel with 500.000000Ohm\n"
+    );
+    let held = TheveninEquivalent {
+        resistance: 0.0,
+        ..equivalent
+    };
+    assert!(cli::format_thevenin(&held)
+        .ends_with("Norton: a-0: none, the nodes are held at a fixed voltage\n"));
+}
+
+#[test]
+fn format_mesh_against_the_nodal_solution() {
+    let mut solver = solver(DIVIDER);
+    let result = solver.mesh().unwrap();
+    let x = solver.solve_system().unwrap();
+    let nodal = Solution::new(&solver.circuit, x).unwrap();
+    let report = cli::format_mesh(&result, &nodal);
+    assert!(report.starts_with("Mesh 0: "), "{report}");
+    assert!(report.ends_with("(agrees)\n"), "{report}");
+}
+
+#[test]
+fn format_tables_with_a_row_per_point() {
+    let mut solver = solver(DIVIDER);
+    let sweep = DcSweep::Linear {
+        start: 0.0,
+        stop: 10.0,
+        step: 5.0,
+    };
+    let result = solver.dc_sweep("V1", &sweep).unwrap();
+    let table = cli::format_dc_sweep(&solver, &result);
+    let rows: Vec<&str> = table.lines().collect();
+    assert_eq!(rows.len(), 4);
+    assert!(rows[0].contains("V(mid)") && rows[0].contains("I(R1.0)"));
+    assert!(rows[3].trim_start().starts_with("1.0000e1"), "{table}");
+
+    let result = solver
+        .transient(1e-3, 1e-4, IntegrationMethod::BackwardEuler)
+        .unwrap();
+    let table = cli::format_waveforms(&solver, &result);
+    assert_eq!(table.lines().count(), result.times.len() + 1);
+
+    let result = solver.superposition().unwrap();
+    let table = cli::format_superposition(&solver, &result);
+    assert!(table.lines().next().unwrap().ends_with("V1        total"));
+}
@@ -0,0 +1,160 @@
+use std::sync::Once;
+
+use nalgebra::Complex;
+use rustic::components::{
+    register_component, Branch,
+    BranchKind::Conductive,
+    ComponentTrait, Connection,
+    Connection::*,
+    ConnectionType::{self, *},
+    Identifer,
+};
+use rustic::error::CircuitError;
+
+/*
+* A conductor described by its conductance, I = G * (V1 - V2), registered as "conductance".
+*/
+#[derive(Debug, Clone)]
+struct Conductance {
+    identifer: Identifer,
+    conductance: f64,
+    // a column outside of its stamp the equation writes to, set with LEAK=column
+    leak: Option<usize>,
+    node1: Connection,
+    node2: Connection,
+}
+
+fn conductance_factory(
+    identifer: Identifer,
+    parameters: &[(String, f64)],
+) -> Result<Box<dyn ComponentTrait>, CircuitError> {
+    let parameter = |name: &str| {
+        parameters
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| *value)
+    };
+    let conductance =
+        parameter("g").ok_or_else(|| CircuitError::InvalidParameter("G is missing".to_string()))?;
+    Ok(Box::new(Conductance {
+        identifer,
+        conductance,
+        leak: parameter("leak").map(|column| column as usize),
+        node1: Disconnected(Anode),
+        node2: Disconnected(Cathode),
+    }))
+}
+
+impl ComponentTrait for Conductance {
+    fn get_id(&self) -> usize {
+        self.identifer.id
+    }
+
+    fn get_name(&self) -> String {
+        self.identifer.name.clone()
+    }
+
+    fn connect(
+        &mut self,
+        node: usize,
+        connection_type: ConnectionType,
+    ) -> Result<(), CircuitError> {
+        match connection_type {
+            Anode => self.node1 = Connected(node, Anode),
+            Cathode => self.node2 = Connected(node, Cathode),
+            _ => return Err(CircuitError::invalid_pin(&self.identifer, connection_type)),
+        }
+        Ok(())
+    }
+
+    fn disconnect(&mut self, connection_type: ConnectionType) -> Result<(), CircuitError> {
+        match connection_type {
+            Anode => self.node1 = Disconnected(Anode),
+            Cathode => self.node2 = Disconnected(Cathode),
+            _ => return Err(CircuitError::invalid_pin(&self.identifer, connection_type)),
+        }
+        Ok(())
+    }
+
+    fn get_connection(&self, connection_type: ConnectionType) -> Result<Connection, CircuitError> {
+        match connection_type {
+            Anode => Ok(self.node1),
+            Cathode => Ok(self.node2),
+            _ => Err(CircuitError::invalid_pin(&self.identifer, connection_type)),
+        }
+    }
+
+    fn connection_types(&self) -> &'static [ConnectionType] {
+        &[Anode, Cathode]
+    }
+
+    fn branches(&self) -> Vec<Branch> {
+        vec![Branch::new(Conductive, Anode, Cathode)]
+    }
+
+    fn current_representative(
+        &self,
+        index: usize,
+        conn_type: ConnectionType,
+        eq: &mut [f64],
+    ) -> Result<(), CircuitError> {
+        match conn_type {
+            Anode => eq[index] = -1.0,
+            Cathode => eq[index] = 1.0,
+            _ => return Err(CircuitError::invalid_pin(&self.identifer, conn_type)),
+        }
+        Ok(())
+    }
+
+    fn num_eq(&self) -> usize {
+        1
+    }
+
+    fn equation(
+        &self,
+        offset: usize,
+        equation: &mut [f64],
+        _eq_id: usize,
+    ) -> Result<f64, CircuitError> {
+        // G * (V1 - V2) - I = 0
+        let v1 = self.node1.node_id(&self.identifer)?;
+        let v2 = self.node2.node_id(&self.identifer)?;
+        equation[v1] = self.conductance;
+        equation[v2] = -self.conductance;
+        equation[offset] = -1.0;
+        if let Some(column) = self.leak {
+            equation[column] = 1.0;
+        }
+        Ok(0.0)
+    }
+
+    fn ac_equation(
+        &self,
+        offset: usize,
+        equation: &mut [Complex<f64>],
+        eq_id: usize,
+        _omega: f64,
+    ) -> Result<Complex<f64>, CircuitError> {
+        let mut real = vec![0.0; equation.len()];
+        let rhs = self.equation(offset, &mut real, eq_id)?;
+        for (c, r) in equation.iter_mut().zip(real) {
+            *c = Complex::from(r);
+        }
+        Ok(Complex::from(rhs))
+    }
+
+    fn registered_type(&self) -> Option<&str> {
+        Some("conductance")
+    }
+
+    fn parameters(&self) -> Vec<(String, f64)> {
+        let mut parameters = vec![("G".to_string(), self.conductance)];
+        parameters.extend(self.leak.map(|column| ("LEAK".to_string(), column as f64)));
+        parameters
+    }
+}
+
+pub fn register() {
+    static REGISTER: Once = Once::new();
+    REGISTER.call_once(|| register_component("conductance", conductance_factory).unwrap());
+}
@@ -0,0 +1,48 @@
+use rustic::netlist::parse;
+use rustic::{Solution, Solver};
+
+fn solve(input: &str) -> Solution {
+    let mut solver = Solver::new(parse("test".to_string(), input).expect("the netlist parses"));
+    solver.solve().expect("the circuit solves").clone()
+}
+
+fn assert_close(actual: f64, expected: f64) {
+    assert!(
+        (actual - expected).abs() <= 1e-9 * expected.abs().max(1.0),
+        "expected {expected}, got {actual}"
+    );
+}
+
+// the control voltage V(p) - V(n) is 2V - 0.5V = 1.5V
+const CONTROL: &str = "V1 p 0 2\nV2 n 0 0.5\nR1 p 0 1k\nR2 n 0 1k\n";
+
+#[test]
+fn vcvs_output_is_gain_times_the_control_voltage() {
+    let solution = solve(&format!(
+        "{CONTROL}E1 out 0 p n 3\nR3 out 0 1k\nE2 rev 0 n p 3\nR4 rev 0 1k\n"
+    ));
+    assert_close(solution.voltage("out").unwrap(), 3.0 * 1.5);
+    // reversed control pins flip the sign
+    assert_close(solution.voltage("rev").unwrap(), -3.0 * 1.5);
+}
+
+#[test]
+fn vcvs_output_is_relative_to_its_negative_pin() {
+    let solution = solve(&format!(
+        "{CONTROL}V3 m 0 1\nR3 m 0 1k\nE1 out m p n -2\nR4 out 0 1k\n"
+    ));
+    assert_close(solution.voltage("out").unwrap() - 1.0, -2.0 * 1.5);
+}
+
+#[test]
+fn vccs_current_is_transconductance_times_the_control_voltage() {
+    // gm * Vc flows from 0 through G1 into out and back to ground through R3
+    let solution = solve(&format!(
+        "{CONTROL}G1 0 out p n 2m\nR3 out 0 1k\nG2 0 rev n p 2m\nR4 rev 0 1k\n"
+    ));
+    assert_close(solution.branch_currents("G1").unwrap()[0], 2e-3 * 1.5);
+    assert_close(solution.voltage("out").unwrap(), 2e-3 * 1.5 * 1e3);
+    // reversed control pins flip the sign
+    assert_close(solution.branch_currents("G2").unwrap()[0], -2e-3 * 1.5);
+    assert_close(solution.voltage("rev").unwrap(), -2e-3 * 1.5 * 1e3);
+}
@@ -0,0 +1,120 @@
+use rustic::analysis::DcSweep;
+use rustic::netlist::parse;
+use rustic::{CircuitError, Solver};
+
+fn solver(input: &str) -> Solver {
+    Solver::new(parse("test".to_string(), input).expect("the netlist parses"))
+}
+
+fn linear(start: f64, stop: f64, step: f64) -> DcSweep {
+    DcSweep::Linear { start, stop, step }
+}
+
+fn assert_all_close(actual: &[f64], expected: &[f64]) {
+    assert_eq!(actual.len(), expected.len(), "{actual:?}");
+    for (a, e) in actual.iter().zip(expected) {
+        assert!(
+            (a - e).abs() <= 1e-9 * e.abs().max(1.0),
+            "expected {expected:?}, got {actual:?}"
+        );
+    }
+}
+
+const DIVIDER: &str = "V1 in 0 5\nR1 in out 1k\nR2 out 0 1k\n";
+
+#[test]
+fn linear_sweep_includes_the_stop_value() {
+    let values = linear(0.0, 1.0, 0.1).values().unwrap();
+    assert_eq!(values.len(), 11);
+    assert_eq!(values[0], 0.0);
+    assert!((values[10] - 1.0).abs() < 1e-12);
+}
+
+#[test]
+fn linear_sweep_stops_before_a_stop_value_it_does_not_reach() {
+    assert_all_close(
+        &linear(0.0, 1.0, 0.3).values().unwrap(),
+        &[0.0, 0.3, 0.6, 0.9],
+    );
+}
+
+#[test]
+fn linear_sweep_counts_down() {
+    assert_all_close(
+        &linear(2.0, 0.0, 0.5).values().unwrap(),
+        &[2.0, 1.5, 1.0, 0.5, 0.0],
+    );
+}
+
+#[test]
+fn linear_sweep_of_a_single_point() {
+    assert_eq!(linear(3.0, 3.0, 1.0).values().unwrap(), [3.0]);
+}
+
+#[test]
+fn invalid_linear_sweeps_are_rejected() {
+    for sweep in [
+        linear(0.0, 1.0, 0.0),
+        linear(0.0, 1.0, -0.1),
+        linear(0.0, f64::INFINITY, 1.0),
+    ] {
+        assert!(matches!(
+            sweep.values(),
+            Err(CircuitError::InvalidParameter(_))
+        ));
+    }
+}
+
+#[test]
+fn sweeping_a_voltage_source() {
+    let mut solver = solver(DIVIDER);
+    let result = solver.dc_sweep("V1", &linear(0.0, 4.0, 1.0)).unwrap();
+    assert_eq!(result.component, "V1");
+    assert_eq!(result.values, [0.0, 1.0, 2.0, 3.0, 4.0]);
+    let out = solver.circuit.find_node("out").unwrap();
+    assert_all_close(&result.node_values(out), &[0.0, 0.5, 1.0, 1.5, 2.0]);
+}
+
+#[test]
+fn sweeping_a_list_of_resistances() {
+    let mut solver = solver(DIVIDER);
+    let values = vec![3e3, 1e3, 9e3];
+    let result = solver
+        .dc_sweep("R2", &DcSweep::List(values.clone()))
+        .unwrap();
+    assert_eq!(result.values, values);
+    let out = solver.circuit.find_node("out").unwrap();
+    let expected: Vec<f64> = values.iter().map(|r| 5.0 * r / (1e3 + r)).collect();
+    assert_all_close(&result.node_values(out), &expected);
+}
+
+#[test]
+fn the_swept_value_is_restored() {
+    let mut solver = solver(DIVIDER);
+    solver.dc_sweep("V1", &linear(0.0, 10.0, 2.5)).unwrap();
+    let v1 = solver.circuit.find_component("V1").unwrap();
+    assert_eq!(solver.circuit.components[v1].sweep_value().unwrap(), 5.0);
+    let out = solver.circuit.find_node("out").unwrap();
+    assert_eq!(solver.solve().unwrap().potentials[out], 2.5);
+}
+
+#[test]
+fn the_swept_value_is_restored_after_a_failed_sweep() {
+    let mut solver = solver("V1 a 0 5\nR1 a b 1k\nD1 b 0\n");
+    solver.newton.max_iterations = 1;
+    assert_eq!(
+        solver
+            .dc_sweep("V1", &DcSweep::List(vec![1.0, 2.0]))
+            .unwrap_err(),
+        CircuitError::NoConvergence(1)
+    );
+    let v1 = solver.circuit.find_component("V1").unwrap();
+    assert_eq!(solver.circuit.components[v1].sweep_value().unwrap(), 5.0);
+}
+
+#[test]
+fn components_without_a_value_cannot_be_swept() {
+    let mut solver = solver("V1 a 0 5\nR1 a b 1k\nD1 b 0\n");
+    assert!(solver.dc_sweep("D1", &linear(0.0, 1.0, 0.5)).is_err());
+    assert!(solver.dc_sweep("X9", &linear(0.0, 1.0, 0.5)).is_err());
+}
@@ -0,0 +1,121 @@
+use rustic::analysis::{AcSweep, DcSweep};
+use rustic::circuit::Circuit;
+use rustic::components::{ConnectionType::Cathode, IntegrationMethod, OpAmpComponent, OpAmpModel};
+use rustic::json::{from_json, to_json, Document};
+use rustic::netlist::parse;
+use rustic::{Solution, Solver};
+
+mod common;
+
+fn circuit(input: &str) -> Circuit {
+    common::register();
+    parse("test".to_string(), input).expect("the netlist parses")
+}
+
+fn solve(circuit: Circuit) -> Solution {
+    let mut solver = Solver::new(circuit);
+    let x = solver.solve_system().expect("the circuit solves");
+    Solution::new(&solver.circuit, x).unwrap()
+}
+
+/*
+* Writes a document, reads it back and checks that writing it again gives the same text.
+*/
+fn round_trip<T: Document>(value: &T) -> T {
+    let text = to_json(value).unwrap();
+    let read: T = from_json(&text).unwrap();
+    assert_eq!(to_json(&read).unwrap(), text);
+    read
+}
+
+const MIXED: &str = "V1 in 0 5\nR1 in a 1k\nX1 a b conductance G=1m\nR2 b 0 2k\n\
+                     C1 b 0 1u IC=1\nL1 a out 1m\nR3 out 0 1k\nF1 0 c V1 2\nR4 c 0 10\n";
+
+#[test]
+fn circuit_round_trips_and_solves_the_same() {
+    let original = circuit(MIXED);
+    let read = round_trip(&original);
+    let (original, read) = (solve(original), solve(read));
+    for node in ["in", "a", "b", "out", "c"] {
+        assert_eq!(original.voltage(node).unwrap(), read.voltage(node).unwrap());
+    }
+}
+
+#[test]
+fn solver_state_is_not_saved() {
+    let mut solver = Solver::new(circuit(
+        "V1 a 0 5\nD1 a b\nR1 b 0 1k\nC1 b 0 1u\nF1 0 c V1 2\nR2 c 0 1\n\
+         O1 b d d GAIN=10 VMAX=1\nR3 d 0 1k\n",
+    ));
+    solver.solve().unwrap();
+    let text = to_json(&solver.circuit).unwrap();
+    for field in ["junction_voltage", "control_index", "\"mode\"", "region"] {
+        assert!(!text.contains(field), "{field} is saved");
+    }
+    // the control index is set again when the circuit is read
+    let read: Circuit = from_json(&text).unwrap();
+    solve(read);
+}
+
+fn op_amp_model(circuit: &Circuit) -> OpAmpModel {
+    circuit
+        .components
+        .iter()
+        .find_map(|c| match c {
+            OpAmpComponent(op_amp) => Some(op_amp.model),
+            _ => None,
+        })
+        .expect("the circuit has an operational amplifier")
+}
+
+#[test]
+fn op_amp_infinite_rails_round_trip() {
+    let original = circuit("V1 in 0 1\nO1 in out out GAIN=10\nR1 out 0 1k\n");
+    let text = to_json(&original).unwrap();
+    assert!(text.contains("\"v_max\": null"), "{text}");
+    let read: Circuit = from_json(&text).unwrap();
+    assert_eq!(op_amp_model(&read), op_amp_model(&original));
+    let solution = solve(read);
+    let out = solution.voltage("out").unwrap();
+    assert!((out - 10.0 / 11.0).abs() < 1e-9, "{out}");
+}
+
+#[test]
+fn solution_round_trips() {
+    let solution = solve(circuit(MIXED));
+    let read = round_trip(&solution);
+    assert_eq!(solution.voltage("b").unwrap(), read.voltage("b").unwrap());
+    assert_eq!(
+        solution.current("X1", Cathode).unwrap(),
+        read.current("X1", Cathode).unwrap()
+    );
+}
+
+#[test]
+fn analysis_results_round_trip() {
+    let mut solver = Solver::new(circuit(MIXED));
+    round_trip(&solver.solve().unwrap().power_report().unwrap());
+    round_trip(
+        &solver
+            .transient(1e-3, 1e-4, IntegrationMethod::Trapezoidal)
+            .unwrap(),
+    );
+    round_trip(&solver.ac(AcSweep::Decade(5), 10.0, 1e4).unwrap());
+    round_trip(
+        &solver
+            .dc_sweep(
+                "V1",
+                &DcSweep::Linear {
+                    start: 0.0,
+                    stop: 5.0,
+                    step: 1.0,
+                },
+            )
+            .unwrap(),
+    );
+    round_trip(&solver.superposition().unwrap());
+    round_trip(&solver.thevenin("out", "0").unwrap());
+
+    let solver = Solver::new(circuit("V1 a 0 5\nR1 a b 1k\nX1 b 0 conductance G=1m\n"));
+    round_trip(&solver.mesh().unwrap());
+}
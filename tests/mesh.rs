@@ -0,0 +1,68 @@
+use rustic::analysis::MeshResult;
+use rustic::netlist::parse;
+use rustic::{Solution, Solver};
+
+/*
+* Runs the mesh and the nodal analysis of a netlist and checks that they agree.
+*/
+fn mesh_and_nodal(input: &str) -> (MeshResult, Solution) {
+    let mut solver = Solver::new(parse("test".to_string(), input).expect("the netlist parses"));
+    let mesh = solver.mesh().expect("the mesh analysis succeeds");
+    let solution = solver.solve().expect("the circuit solves").clone();
+    assert!(
+        mesh.agrees_with(&solution),
+        "the mesh analysis deviates by {}",
+        mesh.deviation(&solution)
+    );
+    (mesh, solution)
+}
+
+fn assert_close(actual: f64, expected: f64) {
+    assert!(
+        (actual - expected).abs() <= 1e-9 * expected.abs().max(1.0),
+        "expected {expected}, got {actual}"
+    );
+}
+
+#[test]
+fn voltage_source_in_a_loop() {
+    // the source sits in the loop between two meshes instead of to ground
+    let (mesh, solution) =
+        mesh_and_nodal("V1 a 0 10\nR1 a b 1k\nV2 b c 2\nR2 c 0 1k\nR3 a c 2k\nR4 b 0 3k\n");
+    assert_eq!(mesh.loops.len(), 3);
+    assert_close(solution.voltage("a").unwrap(), 10.0);
+}
+
+#[test]
+fn current_source_shared_by_two_meshes() {
+    // I1 sits in the middle branch, so it belongs to both windows of the circuit
+    let (_, solution) =
+        mesh_and_nodal("V1 a 0 12\nR1 a b 1k\nI1 b c 2m\nR2 b 0 2k\nR3 c 0 3k\nR4 a c 4k\n");
+    // KCL at c: (12 - c) / 4k + 2m = c / 3k
+    let c = (12.0 / 4e3 + 2e-3) / (1.0 / 4e3 + 1.0 / 3e3);
+    assert_close(solution.voltage("c").unwrap(), c);
+}
+
+#[test]
+fn voltage_controlled_sources() {
+    mesh_and_nodal("V1 a 0 1\nR1 a b 1k\nR2 b 0 1k\nE1 c 0 b 0 4\nR3 c d 2k\nR4 d 0 2k\n");
+    mesh_and_nodal("V1 a 0 1\nR1 a 0 1k\nG1 b 0 a 0 1m\nR2 b 0 3k\nR3 b a 1k\n");
+}
+
+#[test]
+fn current_controlled_sources() {
+    mesh_and_nodal("V1 a 0 5\nR1 a b 1k\nR2 b 0 1k\nH1 c 0 V1 100\nR3 c 0 1k\n");
+    mesh_and_nodal("V1 a 0 5\nR1 a b 1k\nR2 b 0 1k\nF1 c 0 V1 3\nR3 c 0 1k\nR4 c b 2k\n");
+}
+
+#[test]
+fn reactive_components_at_the_operating_point() {
+    // the capacitor is open and the inductor a short at DC
+    mesh_and_nodal("V1 a 0 3\nR1 a b 1k\nC1 b 0 1u\nL1 b c 1m\nR2 c 0 2k\n");
+}
+
+#[test]
+fn nonlinear_circuits_are_rejected() {
+    let solver = Solver::new(parse("test".to_string(), "V1 a 0 1\nD1 a 0\n").unwrap());
+    assert!(solver.mesh().is_err());
+}
@@ -0,0 +1,158 @@
+use rustic::components::{CCCurrentSourceComponent, CCVoltageSourceComponent};
+use rustic::netlist::{parse, parse_value, GROUND_COMPONENT};
+use rustic::{Solution, Solver};
+
+fn solve(input: &str) -> Solution {
+    let circuit = parse("test".to_string(), input).expect("the netlist parses");
+    let mut solver = Solver::new(circuit);
+    let x = solver.solve_system().expect("the circuit solves");
+    Solution::new(&solver.circuit, x).unwrap()
+}
+
+fn assert_close(actual: f64, expected: f64) {
+    assert!(
+        (actual - expected).abs() <= 1e-9 * expected.abs().max(1.0),
+        "expected {expected}, got {actual}"
+    );
+}
+
+#[test]
+fn parse_value_reads_plain_numbers() {
+    assert_eq!(parse_value("10"), Some(10.0));
+    assert_eq!(parse_value("-2.5"), Some(-2.5));
+    assert_eq!(parse_value("+.5"), Some(0.5));
+    assert_eq!(parse_value("2e-3"), Some(2e-3));
+    assert_eq!(parse_value("1E3"), Some(1e3));
+}
+
+#[test]
+fn parse_value_applies_scale_factors() {
+    assert_close(parse_value("4.7k").unwrap(), 4.7e3);
+    assert_close(parse_value("10u").unwrap(), 10e-6);
+    assert_close(parse_value("1meg").unwrap(), 1e6);
+    assert_close(parse_value("1MEG").unwrap(), 1e6);
+    assert_close(parse_value("3m").unwrap(), 3e-3);
+    assert_close(parse_value("2mil").unwrap(), 50.8e-6);
+    assert_close(parse_value("5p").unwrap(), 5e-12);
+    assert_close(parse_value("1t").unwrap(), 1e12);
+}
+
+#[test]
+fn parse_value_ignores_units_after_the_scale() {
+    assert_close(parse_value("1kohm").unwrap(), 1e3);
+    assert_close(parse_value("10V").unwrap(), 10.0);
+    assert_close(parse_value("100nF").unwrap(), 100e-9);
+    // an e without digits is a unit, not an exponent
+    assert_close(parse_value("1e").unwrap(), 1.0);
+}
+
+#[test]
+fn parse_value_rejects_invalid_numbers() {
+    assert_eq!(parse_value(""), None);
+    assert_eq!(parse_value("k"), None);
+    assert_eq!(parse_value("-"), None);
+    assert_eq!(parse_value("1%"), None);
+}
+
+#[test]
+fn divider_solves() {
+    let solution = solve("V1 in 0 10\nR1 in out 1k\nR2 out 0 3k\n.end\n");
+    assert_close(solution.voltage("in").unwrap(), 10.0);
+    assert_close(solution.voltage("out").unwrap(), 7.5);
+    assert_close(solution.voltage("0").unwrap(), 0.0);
+}
+
+#[test]
+fn comments_and_everything_after_end_are_ignored() {
+    let solution = solve(
+        "* a comment line\nV1 in 0 10 ; a trailing comment\nR1 in 0 1k\n.end\nR2 in 0 not parsed\n",
+    );
+    assert_close(solution.voltage("in").unwrap(), 10.0);
+    assert!(solution.circuit().find_component("R2").is_err());
+}
+
+#[test]
+fn continuation_lines_extend_the_previous_card() {
+    let separate = solve("V1 in 0 10\nR1 in\n+ out 1k\nR2 out\n+ 0\n+ 1k\n");
+    assert_close(separate.voltage("out").unwrap(), 5.0);
+
+    // the rest of a word glued to the plus sign is its first token
+    let glued = solve("V1 in 0 10\nR1 in out\n+1k\nR2 out 0 1k\n");
+    assert_close(glued.voltage("out").unwrap(), 5.0);
+}
+
+#[test]
+fn gnd_is_the_reference_node() {
+    let solution = solve("V1 in gnd 5\nR1 in GND 1k\n");
+    assert_close(solution.voltage("in").unwrap(), 5.0);
+    assert_eq!(solution.circuit().nodes.len(), 2);
+}
+
+#[test]
+fn a_g_element_named_gnd_does_not_clash_with_ground() {
+    let solution = solve("V1 a 0 2\nR1 a 0 1k\nGND 0 b a 0 1m\nR2 b 0 1k\n");
+    assert_close(solution.voltage("b").unwrap(), 2.0);
+    assert_close(solution.branch_currents("GND").unwrap()[0], 2e-3);
+    assert_eq!(solution.circuit().find_component(GROUND_COMPONENT), Ok(1));
+}
+
+#[test]
+fn component_names_are_not_case_sensitive() {
+    let error = parse("test".to_string(), "V1 a 0 1\nR1 a 0 1k\nr1 a 0 2k\n").unwrap_err();
+    assert_eq!((error.line, error.column), (3, 1));
+    assert!(error.message.contains("line 2"), "{}", error.message);
+}
+
+#[test]
+fn errors_point_at_the_offending_token() {
+    let error = parse("test".to_string(), "V1 in 0 10\nR1 in 0 abc\n").unwrap_err();
+    assert_eq!((error.line, error.column), (2, 9));
+
+    let error = parse("test".to_string(), "V1 in 0 10\nR1 in\n").unwrap_err();
+    assert_eq!(error.line, 2);
+
+    let error = parse(
+        "test".to_string(),
+        "V1 in 0 10\n  R1 in 0 1k\n  R1 in 0 2k\n",
+    )
+    .unwrap_err();
+    assert_eq!((error.line, error.column), (3, 3));
+    assert!(error.message.contains("line 2"), "{}", error.message);
+}
+
+#[test]
+fn topology_errors_point_at_the_first_component_causing_them() {
+    let error = parse(
+        "test".to_string(),
+        "V1 a 0 1\nR1 a 0 1k\nI1 b c 1m\nR2 b c 1k\n",
+    )
+    .unwrap_err();
+    assert_eq!((error.line, error.column), (3, 1));
+    assert!(error.to_string().starts_with("3:1: "), "{error}");
+}
+
+#[test]
+fn current_controlled_sources_sense_voltage_sources_like_spice() {
+    // I(V1) flows from a through V1 to 0, -1mA while V1 delivers 1mA to R1
+    let solution = solve(
+        "V1 a 0 1\nR1 a 0 1k\nF1 0 x V1 2\nR2 x 0 1k\nH1 y 0 V1 1k\nR3 y 0 1k\n\
+         F2 0 z R1 2\nR4 z 0 1k\n",
+    );
+    assert_close(solution.voltage("x").unwrap(), -2.0);
+    assert_close(solution.voltage("y").unwrap(), -1.0);
+    // the current of a resistor flows from its first node through it to its second
+    assert_close(solution.voltage("z").unwrap(), 2.0);
+
+    // the gains are kept as they are written
+    let circuit = solution.circuit();
+    let CCCurrentSourceComponent(f1) = &circuit.components[circuit.find_component("F1").unwrap()]
+    else {
+        panic!("F1 is a current controlled current source");
+    };
+    assert_eq!(f1.gain, 2.0);
+    let CCVoltageSourceComponent(h1) = &circuit.components[circuit.find_component("H1").unwrap()]
+    else {
+        panic!("H1 is a current controlled voltage source");
+    };
+    assert_eq!(h1.transresistance, 1e3);
+}
@@ -0,0 +1,70 @@
+use rustic::netlist::parse;
+use rustic::{Solution, Solver};
+
+fn solve(input: &str) -> Solution {
+    let mut solver = Solver::new(parse("test".to_string(), input).expect("the netlist parses"));
+    solver.solve().expect("the circuit solves").clone()
+}
+
+fn assert_close(actual: f64, expected: f64) {
+    assert!(
+        (actual - expected).abs() <= 1e-9 * expected.abs().max(1.0),
+        "expected {expected}, got {actual}"
+    );
+}
+
+#[test]
+fn ideal_follower() {
+    let solution = solve("V1 in 0 2.5\nO1 in out out\nR1 out 0 1k\n");
+    assert_close(solution.voltage("out").unwrap(), 2.5);
+}
+
+#[test]
+fn ideal_inverting_amplifier() {
+    let solution = solve("V1 in 0 0.5\nR1 in m 1k\nR2 m out 4k\nO1 0 m out\nR3 out 0 1k\n");
+    assert_close(solution.voltage("m").unwrap(), 0.0);
+    assert_close(solution.voltage("out").unwrap(), -2.0);
+}
+
+#[test]
+fn finite_gain_follower() {
+    // Vout = A (Vin - Vout)
+    let solution = solve("V1 in 0 2\nO1 in out out GAIN=10\nR1 out 0 1k\n");
+    assert_close(solution.voltage("out").unwrap(), 2.0 * 10.0 / 11.0);
+}
+
+#[test]
+fn finite_gain_inside_the_rails() {
+    let solution = solve("V1 in 0 0.1\nO1 in 0 out GAIN=20 VMIN=-5 VMAX=5\nR1 out 0 1k\n");
+    assert_close(solution.voltage("out").unwrap(), 2.0);
+}
+
+#[test]
+fn saturated_high_and_low() {
+    let solution = solve("V1 in 0 1\nO1 in 0 out GAIN=1e5 VMIN=-2 VMAX=5\nR1 out 0 1k\n");
+    assert_close(solution.voltage("out").unwrap(), 5.0);
+    let solution = solve("V1 in 0 -1\nO1 in 0 out GAIN=1e5 VMIN=-2 VMAX=5\nR1 out 0 1k\n");
+    assert_close(solution.voltage("out").unwrap(), -2.0);
+}
+
+#[test]
+fn a_follower_saturates_without_feedback_flipping_the_region() {
+    let solution = solve("V1 in 0 3\nO1 in out out GAIN=1e5 VMAX=1\nR1 out 0 1k\n");
+    assert_close(solution.voltage("out").unwrap(), 1.0);
+}
+
+#[test]
+fn an_operating_point_at_a_rail_converges() {
+    // the linear output of a follower is A / (A + 1) * Vin, which is the rail for
+    // Vin = (A + 1) / A, rounding errors put it just above or below the rail
+    for gain in [1.74, 3.96, 4.33] {
+        let rail_input: f64 = (gain + 1.0) / gain;
+        for ulps in -2i64..=2 {
+            let input = f64::from_bits((rail_input.to_bits() as i64 + ulps) as u64);
+            let solution = solve(&format!(
+                "V1 in 0 {input:e}\nO1 in out out GAIN={gain} VMAX=1 VMIN=-1\nR1 out 0 1k\n"
+            ));
+            assert_close(solution.voltage("out").unwrap(), 1.0);
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use rustic::analysis::PowerReport;
+use rustic::netlist::parse;
+use rustic::{Solution, Solver};
+
+fn solve(input: &str) -> Solution {
+    let mut solver = Solver::new(parse("test".to_string(), input).expect("the netlist parses"));
+    solver.solve().expect("the circuit solves").clone()
+}
+
+fn assert_close(actual: f64, expected: f64) {
+    assert!(
+        (actual - expected).abs() <= 1e-9 * expected.abs().max(1e-3),
+        "expected {expected}, got {actual}"
+    );
+}
+
+#[test]
+fn a_delivering_and_a_charging_source() {
+    // 6mA flow from V1 through R1 into the positive pin of V2, which is being charged
+    let solution = solve("V1 a 0 10\nR1 a b 1k\nV2 b 0 4\n");
+    assert_close(solution.power("V1").unwrap(), -0.06);
+    assert_close(solution.power("V2").unwrap(), 0.024);
+    assert_close(solution.power("R1").unwrap(), 0.036);
+
+    let report = solution.power_report().unwrap();
+    assert_close(report.sourced, 0.06);
+    assert_close(report.dissipated, 0.06);
+    assert!(report.is_balanced(), "{report:?}");
+}
+
+#[test]
+fn a_current_source_delivers_power() {
+    // 2mA into c raise it to 2V
+    let solution = solve("I1 0 c 2m\nR1 c 0 1k\n");
+    assert_close(solution.power("I1").unwrap(), -4e-3);
+    assert_close(solution.power("R1").unwrap(), 4e-3);
+    assert!(solution.power_report().unwrap().is_balanced());
+}
+
+#[test]
+fn circuits_with_controlled_and_nonlinear_components_are_balanced() {
+    for input in [
+        "V1 a 0 5\nR1 a b 1k\nD1 b 0\n",
+        "V1 a 0 2\nR1 a 0 1k\nE1 b 0 a 0 3\nR2 b c 2k\nR3 c 0 1k\nG1 0 c a 0 1m\n",
+        "V1 a 0 3\nR1 a b 1k\nR2 b 0 2k\nF1 0 c V1 2\nR3 c 0 500\nH1 d 0 V1 1k\nR4 d 0 1k\n",
+        "V1 in 0 0.5\nR1 in m 1k\nR2 m out 4k\nO1 0 m out\nR3 out 0 1k\n",
+    ] {
+        let report = solve(input).power_report().unwrap();
+        assert!(report.is_balanced(), "{input}: {report:?}");
+        assert!(report.sourced > 0.0);
+    }
+}
+
+#[test]
+fn an_unbalanced_report() {
+    let report = PowerReport {
+        components: Vec::new(),
+        sourced: 1.0,
+        dissipated: 0.9,
+    };
+    assert!(!report.is_balanced());
+    assert_close(report.balance(), 0.1);
+}
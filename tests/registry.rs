@@ -0,0 +1,54 @@
+use rustic::circuit::Circuit;
+use rustic::components::{register_component, ConnectionType::Anode};
+use rustic::json::{from_json, to_json};
+use rustic::netlist::parse;
+use rustic::{Solution, Solver};
+
+mod common;
+
+const DIVIDER: &str = "V1 in 0 10\nR1 in out 1k\nX1 out 0 conductance G=3m\n";
+
+fn solve(circuit: Circuit) -> Solution {
+    let mut solver = Solver::new(circuit);
+    let x = solver.solve_system().expect("the circuit solves");
+    Solution::new(&solver.circuit, x).unwrap()
+}
+
+#[test]
+fn a_registered_component_is_used_by_an_x_card() {
+    common::register();
+    let solution = solve(parse("test".to_string(), DIVIDER).unwrap());
+    // 1k in series with 1/3m = 333.3 ohm
+    let out = solution.voltage("out").unwrap();
+    assert!((out - 2.5).abs() < 1e-9, "{out}");
+    let current = solution.current("X1", Anode).unwrap();
+    assert!((current - 7.5e-3).abs() < 1e-12, "{current}");
+}
+
+#[test]
+fn a_registered_component_round_trips_through_json() {
+    common::register();
+    let circuit = parse("test".to_string(), DIVIDER).unwrap();
+    let text = to_json(&circuit).unwrap();
+    assert!(
+        text.contains("\"registered_type\": \"conductance\""),
+        "{text}"
+    );
+    let read: Circuit = from_json(&text).unwrap();
+    assert_eq!(to_json(&read).unwrap(), text);
+    let out = solve(read).voltage("out").unwrap();
+    assert!((out - 2.5).abs() < 1e-9, "{out}");
+}
+
+#[test]
+fn registry_errors() {
+    common::register();
+    assert!(register_component("conductance", |_, _| unreachable!()).is_err());
+
+    let error = parse("test".to_string(), "V1 a 0 1\nX1 a 0 unknown\n").unwrap_err();
+    assert_eq!((error.line, error.column), (2, 8));
+    let error = parse("test".to_string(), "V1 a 0 1\nX1 a conductance G=1\n").unwrap_err();
+    assert_eq!(error.line, 2);
+    let error = parse("test".to_string(), "V1 a 0 1\nX1 a 0 conductance\n").unwrap_err();
+    assert!(error.message.contains("G is missing"), "{}", error.message);
+}
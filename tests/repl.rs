@@ -0,0 +1,148 @@
+use std::path::PathBuf;
+
+use rustic::repl::Shell;
+
+fn run(shell: &mut Shell, commands: &str) {
+    for line in commands.lines() {
+        shell
+            .execute(line)
+            .unwrap_or_else(|e| panic!("{line}: {e}"));
+    }
+}
+
+/*
+* A file in the temporary directory that is removed when it goes out of scope.
+*/
+struct TempFile(PathBuf);
+
+impl TempFile {
+    fn new(name: &str) -> Self {
+        let file = format!("rustic-repl-{}-{name}", std::process::id());
+        Self(std::env::temp_dir().join(file))
+    }
+
+    fn path(&self) -> &str {
+        self.0.to_str().unwrap()
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+// a 10V source divided by two 1k resistors, the cathode of a voltage source is its positive pin
+const DIVIDER: &str = "add V1 10\nadd R1 1k\nadd R2 1k\nconnect V1.cathode in\n\
+                       connect V1.anode 0\nconnect R1.anode in\nconnect R1.cathode mid\n\
+                       connect R2.anode mid\nconnect R2.cathode gnd";
+
+fn mid_voltage(shell: &Shell) -> f64 {
+    shell
+        .solution()
+        .expect("the circuit is solved")
+        .voltage("mid")
+        .unwrap()
+}
+
+#[test]
+fn add_connect_and_solve() {
+    let mut shell = Shell::new();
+    run(&mut shell, DIVIDER);
+    assert!(shell.solution().is_none());
+    shell.execute("solve").unwrap();
+    assert!((mid_voltage(&shell) - 5.0).abs() < 1e-12);
+    // the shell keeps its circuit open for more commands
+    assert!(!shell.circuit().is_locked());
+}
+
+#[test]
+fn commands_are_checked() {
+    let mut shell = Shell::new();
+    assert!(shell.execute("frobnicate").is_err());
+    assert!(shell.execute("add X1 1k").is_err());
+    assert!(shell.execute("add R1").is_err());
+    shell.execute("add R1 1k").unwrap();
+    assert!(shell.execute("add R1 2k").is_err());
+    assert!(shell.execute("connect R1.gate a").is_err());
+    assert!(shell.execute("print nodes").is_err());
+    // comments and empty lines do nothing
+    shell.execute("* a comment").unwrap();
+    shell.execute("").unwrap();
+}
+
+#[test]
+fn save_and_load_round_trip() {
+    let file = TempFile::new("round-trip");
+    let mut shell = Shell::new();
+    run(&mut shell, DIVIDER);
+    shell.execute("solve").unwrap();
+    shell.execute(&format!("save {}", file.path())).unwrap();
+
+    let mut loaded = Shell::new();
+    loaded.execute(&format!("load {}", file.path())).unwrap();
+    assert_eq!(
+        loaded.circuit().components.len(),
+        shell.circuit().components.len()
+    );
+    assert_eq!(loaded.circuit().nodes.len(), shell.circuit().nodes.len());
+    loaded.execute("solve").unwrap();
+    assert_eq!(mid_voltage(&loaded), mid_voltage(&shell));
+}
+
+#[test]
+fn load_accepts_commands_in_any_case() {
+    let file = TempFile::new("upper-case");
+    std::fs::write(
+        file.path(),
+        "ADD V1 1\nAdd R1 1k\nCONNECT V1.cathode a\nConnect V1.anode 0\n\
+         connect R1.anode a\nconnect R1.cathode 0\n",
+    )
+    .unwrap();
+    let mut shell = Shell::new();
+    shell.execute(&format!("LOAD {}", file.path())).unwrap();
+    assert_eq!(shell.circuit().components.len(), 3);
+}
+
+#[test]
+fn a_failed_load_keeps_the_old_circuit() {
+    let file = TempFile::new("broken");
+    std::fs::write(file.path(), "add R9 1k\nconnect R9.anode x\nadd R9 2k\n").unwrap();
+    let mut shell = Shell::new();
+    run(&mut shell, DIVIDER);
+    shell.execute("solve").unwrap();
+
+    let error = shell
+        .execute(&format!("load {}", file.path()))
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains(":3:"), "{error}");
+    assert!(shell.circuit().find_component("R9").is_err());
+    assert!(shell.circuit().find_component("R2").is_ok());
+    assert!((mid_voltage(&shell) - 5.0).abs() < 1e-12);
+
+    // only commands that build a circuit can be loaded
+    std::fs::write(file.path(), "add R9 1k\nsolve\n").unwrap();
+    let error = shell
+        .execute(&format!("load {}", file.path()))
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains(":2:"), "{error}");
+}
+
+#[test]
+fn toggle_clears_the_stale_solution() {
+    let mut shell = Shell::new();
+    run(
+        &mut shell,
+        "add V1 10\nadd S1\nadd R1 1k\nadd R2 3k\nconnect V1.cathode in\nconnect V1.anode 0\n\
+         connect S1.middle in\nconnect S1.left l\nconnect S1.right r\nconnect R1.anode l\n\
+         connect R1.cathode 0\nconnect R2.anode r\nconnect R2.cathode 0",
+    );
+    shell.execute("solve").unwrap();
+    assert!(shell.solution().is_some());
+    shell.execute("toggle S1").unwrap();
+    assert!(shell.solution().is_none());
+    shell.execute("solve").unwrap();
+    assert!(shell.solution().is_some());
+}
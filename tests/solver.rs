@@ -0,0 +1,66 @@
+use rustic::analysis::AcSweep;
+use rustic::components::ConnectionType::Cathode;
+use rustic::error::CircuitError;
+use rustic::netlist::parse;
+use rustic::{Solution, Solver};
+
+mod common;
+
+fn solve(input: &str) -> Solution {
+    let circuit = parse("test".to_string(), input).expect("the netlist parses");
+    let mut solver = Solver::new(circuit);
+    let x = solver.solve_system().expect("the circuit solves");
+    Solution::new(&solver.circuit, x).unwrap()
+}
+
+#[test]
+fn badly_scaled_resistors_are_not_singular() {
+    let solution = solve("V1 a 0 1\nR1 a b 1k\nR2 b 0 1e13\n");
+    let b = solution.voltage("b").unwrap();
+    assert!((b - 1.0).abs() < 1e-6, "expected 1V, got {b}");
+
+    let solution = solve("V1 a 0 1\nR1 a b 1e-3\nR2 b 0 1e15\nR3 b 0 1e15\n");
+    let b = solution.voltage("b").unwrap();
+    assert!((b - 1.0).abs() < 1e-6, "expected 1V, got {b}");
+}
+
+#[test]
+fn diode_forward_biased_by_a_source_solves() {
+    for volts in ["0.7", "2", "5"] {
+        let solution = solve(&format!("V1 a 0 {volts}\nD1 a 0\n"));
+        let current = solution.current("D1", Cathode).unwrap();
+        assert!(current.is_finite() && current != 0.0, "{volts}V: {current}");
+    }
+}
+
+#[test]
+fn entries_outside_the_columns_of_a_component_are_rejected() {
+    common::register();
+    let circuit = parse(
+        "test".to_string(),
+        "V1 a 0 5\nR1 a b 1k\nX1 b 0 conductance G=1m LEAK=0\n",
+    )
+    .unwrap();
+    // column 0 is the potential of a, which X1 is not connected to
+    assert_eq!(circuit.find_node("a").unwrap(), 0);
+
+    let mut solver = Solver::new(circuit);
+    let error = solver.solve_system().unwrap_err();
+    assert!(
+        matches!(error, CircuitError::InvalidStamp { column: 0, .. }),
+        "{error}"
+    );
+    let error = solver.ac(AcSweep::Linear(1), 50.0, 50.0).unwrap_err();
+    assert!(
+        matches!(error, CircuitError::InvalidStamp { column: 0, .. }),
+        "{error}"
+    );
+}
+
+#[test]
+fn diode_with_both_pins_on_one_node_carries_no_current() {
+    let solution = solve("V1 a 0 1\nR1 a 0 1k\nD1 a a\n");
+    let current = solution.current("D1", Cathode).unwrap();
+    assert!(current.abs() < 1e-12, "{current}");
+    assert!((solution.voltage("a").unwrap() - 1.0).abs() < 1e-12);
+}
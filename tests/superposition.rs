@@ -0,0 +1,72 @@
+use rustic::netlist::parse;
+use rustic::{CircuitError, Solver};
+
+fn solver(input: &str) -> Solver {
+    Solver::new(parse("test".to_string(), input).expect("the netlist parses"))
+}
+
+fn assert_all_close(actual: &[f64], expected: &[f64]) {
+    assert_eq!(actual.len(), expected.len());
+    for (a, e) in actual.iter().zip(expected) {
+        assert!(
+            (a - e).abs() <= 1e-9 * e.abs().max(1.0),
+            "expected {expected:?}, got {actual:?}"
+        );
+    }
+}
+
+// two voltage sources, a current source and a controlled source that stays in place
+const SOURCES: &str = "V1 a 0 10\nR1 a b 1k\nR2 b 0 2k\nI1 0 b 3m\nR3 b c 500\n\
+                       V2 c 0 -4\nE1 d 0 b 0 2\nR4 d 0 1k\n";
+
+#[test]
+fn the_contributions_add_up_to_the_operating_point() {
+    let mut solver = solver(SOURCES);
+    let result = solver.superposition().unwrap();
+    assert_eq!(result.sources, ["V1", "I1", "V2"]);
+    let solution = solver.solve().unwrap();
+    assert_all_close(&result.total_potentials(), &solution.potentials);
+    assert_all_close(&result.total_currents(), &solution.currents);
+}
+
+#[test]
+fn a_single_contribution() {
+    // with only I1 on, the voltage sources are shorts and b sees 1k || 2k || 500
+    let mut solver = solver(SOURCES);
+    let result = solver.superposition().unwrap();
+    let b = solver.circuit.find_node("b").unwrap();
+    let parallel = 1.0 / (1.0 / 1e3 + 1.0 / 2e3 + 1.0 / 500.0);
+    assert_all_close(&[result.node_contributions(b)[1]], &[3e-3 * parallel]);
+}
+
+#[test]
+fn the_sources_are_restored() {
+    let mut solver = solver(SOURCES);
+    let before = solver.solve().unwrap().clone();
+    let values = |solver: &Solver| {
+        solver
+            .components()
+            .iter()
+            .map(|c| c.sweep_value().ok())
+            .collect::<Vec<_>>()
+    };
+    let original_values = values(&solver);
+    solver.superposition().unwrap();
+    assert_eq!(values(&solver), original_values);
+    let after = solver.solve().unwrap();
+    assert_eq!(after.potentials, before.potentials);
+    assert_eq!(after.currents, before.currents);
+}
+
+#[test]
+fn nonlinear_circuits_are_rejected() {
+    for input in [
+        "V1 a 0 5\nR1 a b 1k\nD1 b 0\n",
+        "V1 a 0 5\nO1 a 0 b GAIN=10 VMAX=1\nR1 b 0 1k\n",
+    ] {
+        assert!(matches!(
+            solver(input).superposition(),
+            Err(CircuitError::InvalidParameter(_))
+        ));
+    }
+}
@@ -0,0 +1,78 @@
+use rustic::netlist::parse;
+use rustic::{CircuitError, Solver};
+
+fn solver(input: &str) -> Solver {
+    Solver::new(parse("test".to_string(), input).expect("the netlist parses"))
+}
+
+fn assert_close(actual: f64, expected: f64) {
+    assert!(
+        (actual - expected).abs() <= 1e-9 * expected.abs().max(1.0),
+        "expected {expected}, got {actual}"
+    );
+}
+
+#[test]
+fn divider_equivalent() {
+    let equivalent = solver("V1 a 0 10\nR1 a b 1k\nR2 b 0 1k\n")
+        .thevenin("b", "0")
+        .unwrap();
+    assert_close(equivalent.voltage, 5.0);
+    assert_close(equivalent.resistance, 500.0);
+    assert_close(equivalent.norton_current().unwrap(), 0.01);
+    assert_close(equivalent.norton_conductance().unwrap(), 2e-3);
+}
+
+#[test]
+fn a_voltage_source_has_no_norton_equivalent() {
+    let equivalent = solver("V1 a 0 10\nR1 a 0 1k\n").thevenin("a", "0").unwrap();
+    assert_close(equivalent.voltage, 10.0);
+    assert_eq!(equivalent.resistance, 0.0);
+    assert_eq!(equivalent.norton_current(), None);
+    assert_eq!(equivalent.norton_conductance(), None);
+}
+
+#[test]
+fn the_test_source_does_not_clash_with_component_names() {
+    // the test source gets the next id, which is 5 with the ground component
+    let mut solver = solver("V1 a 0 10\nR1 a b 1k\nR2 b 0 1k\nIthevenin5 a 0 0\n");
+    assert_eq!(solver.components().len(), 5);
+    let equivalent = solver.thevenin("b", "0").unwrap();
+    assert_close(equivalent.resistance, 500.0);
+}
+
+#[test]
+fn the_newton_options_of_the_solver_are_used() {
+    let mut solver = solver("V1 a 0 5\nR1 a b 1k\nD1 b 0\n");
+    solver.newton.max_iterations = 1;
+    assert_eq!(
+        solver.thevenin("b", "0"),
+        Err(CircuitError::NoConvergence(1))
+    );
+}
+
+#[test]
+fn nodes_tied_through_a_voltage_source_and_an_inductor_have_no_norton_equivalent() {
+    // b and c are joined by L1 and V2, solving the loaded circuit leaves about 1e-13 ohm
+    let equivalent = solver(
+        "V1 a 0 16.40980811789632\nR1 a b 7990.6013468280435\nL1 b e 1m\n\
+         V2 e c 0.5558446049690247\nR2 c 0 323.660422116518\nR3 a c 85239.44467306137\n\
+         E1 d c a 0 1.1312528047710657\nR4 d b 1.1312528047710657\n",
+    )
+    .thevenin("b", "c")
+    .unwrap();
+    assert_close(equivalent.voltage, 0.5558446049690247);
+    assert_eq!(equivalent.resistance, 0.0);
+    assert_eq!(equivalent.norton_current(), None);
+    assert_eq!(equivalent.norton_conductance(), None);
+}
+
+#[test]
+fn nodes_tied_to_ground_through_sources_in_series() {
+    let equivalent = solver("V1 a 0 10\nV2 b a 2\nR1 b c 1k\nR2 c 0 1k\n")
+        .thevenin("b", "0")
+        .unwrap();
+    assert_close(equivalent.voltage, 12.0);
+    assert_eq!(equivalent.resistance, 0.0);
+    assert_eq!(equivalent.norton_current(), None);
+}
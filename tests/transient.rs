@@ -0,0 +1,136 @@
+use rustic::analysis::TransientResult;
+use rustic::components::IntegrationMethod::{self, *};
+use rustic::netlist::parse;
+use rustic::Solver;
+
+// tau of both circuits, the RC charge with 1k and 1u and the RL rise with 1k and 1H
+const TAU: f64 = 1e-3;
+
+const RC: &str = "V1 in 0 1\nR1 in out 1k\nC1 out 0 1u IC=0.25\n";
+const RL: &str = "V1 in 0 1\nR1 in out 1k\nL1 out 0 1 IC=0\n";
+
+fn transient(
+    input: &str,
+    t_stop: f64,
+    t_step: f64,
+    method: IntegrationMethod,
+) -> (Solver, TransientResult) {
+    let mut solver = Solver::new(parse("test".to_string(), input).expect("the netlist parses"));
+    let result = solver
+        .transient(t_stop, t_step, method)
+        .expect("the transient succeeds");
+    (solver, result)
+}
+
+/*
+* The largest difference between a waveform and a closed form evaluated at the times of the result.
+*/
+fn max_error(result: &TransientResult, waveform: &[f64], exact: impl Fn(f64) -> f64) -> f64 {
+    result
+        .times
+        .iter()
+        .zip(waveform)
+        .map(|(&t, v)| (v - exact(t)).abs())
+        .fold(0.0, f64::max)
+}
+
+fn rc_voltage(t: f64) -> f64 {
+    // charges from the initial 0.25V towards 1V
+    1.0 - 0.75 * (-t / TAU).exp()
+}
+
+fn rl_current(t: f64) -> f64 {
+    1e-3 * (1.0 - (-t / TAU).exp())
+}
+
+/*
+* The tolerances are for a step of tau / 100, backward Euler is first order and trapezoidal
+* integration second order in the step.
+*/
+fn tolerance(method: IntegrationMethod) -> f64 {
+    match method {
+        BackwardEuler => 3e-3,
+        Trapezoidal => 1e-5,
+    }
+}
+
+/*
+* The tolerances for a step of tau / 10.
+*/
+fn coarse_tolerance(method: IntegrationMethod) -> f64 {
+    match method {
+        BackwardEuler => 10.0 * tolerance(method),
+        Trapezoidal => 100.0 * tolerance(method),
+    }
+}
+
+#[test]
+fn rc_charge_follows_the_exponential() {
+    for method in [BackwardEuler, Trapezoidal] {
+        let (solver, result) = transient(RC, 5.0 * TAU, TAU / 100.0, method);
+        let out = solver.circuit.find_node("out").unwrap();
+        let error = max_error(&result, &result.node_waveform(out), rc_voltage);
+        // relative to the 0.75V the capacitor charges by
+        assert!(error <= tolerance(method) * 0.75, "{method:?}: {error}");
+    }
+}
+
+#[test]
+fn rl_current_follows_the_exponential() {
+    for method in [BackwardEuler, Trapezoidal] {
+        let (solver, result) = transient(RL, 5.0 * TAU, TAU / 100.0, method);
+        let inductor = solver.circuit.find_component("L1").unwrap();
+        let index = solver
+            .circuit
+            .get_current_index(solver.get_component(inductor).unwrap())
+            .unwrap()
+            - solver.nodes().len();
+        let error = max_error(&result, &result.current_waveform(index), rl_current);
+        // relative to the 1mA final current
+        assert!(error <= tolerance(method) * 1e-3, "{method:?}: {error}");
+    }
+}
+
+#[test]
+fn starts_at_the_initial_condition() {
+    for method in [BackwardEuler, Trapezoidal] {
+        let (solver, result) = transient(RC, TAU, TAU / 10.0, method);
+        let out = solver.circuit.find_node("out").unwrap();
+        assert_eq!(result.times[0], 0.0);
+        assert!((result.node_waveform(out)[0] - 0.25).abs() < 1e-12);
+    }
+}
+
+#[test]
+fn the_last_step_is_shortened() {
+    for method in [BackwardEuler, Trapezoidal] {
+        let (solver, result) = transient(RC, 1.05e-3, 1e-4, method);
+        // t = 0, ten full steps and a last step of 5e-5
+        assert_eq!(result.len(), 12);
+        assert_eq!(*result.times.last().unwrap(), 1.05e-3);
+        let steps: Vec<f64> = result.times.windows(2).map(|t| t[1] - t[0]).collect();
+        assert!(steps[..10].iter().all(|s| (s - 1e-4).abs() < 1e-15));
+        assert!((steps[10] - 5e-5).abs() < 1e-15);
+
+        let out = solver.circuit.find_node("out").unwrap();
+        let last = *result.node_waveform(out).last().unwrap();
+        assert!(
+            (last - rc_voltage(1.05e-3)).abs() <= coarse_tolerance(method) * 0.75,
+            "{method:?}: {last}"
+        );
+    }
+}
+
+#[test]
+fn a_step_that_divides_the_stop_time_adds_no_extra_point() {
+    let (_, result) = transient(RC, 1e-3, 1e-4, BackwardEuler);
+    assert_eq!(result.len(), 11);
+    assert_eq!(*result.times.last().unwrap(), 1e-3);
+}
+
+#[test]
+fn invalid_parameters_are_rejected() {
+    let mut solver = Solver::new(parse("test".to_string(), RC).unwrap());
+    assert!(solver.transient(1e-3, 0.0, Trapezoidal).is_err());
+    assert!(solver.transient(-1e-3, 1e-4, Trapezoidal).is_err());
+}
@@ -0,0 +1,232 @@
+use rustic::circuit::Circuit;
+use rustic::components::{
+    CCVoltageSource, Capacitor, Component,
+    Component::*,
+    Connection::Connected,
+    ConnectionType::{self, *},
+    DCCurrentSource, DCVoltageSource, Ground, Identifer, Resistor, VCCurrentSource,
+    VCVoltageSource,
+};
+use rustic::validation::TopologyIssue;
+
+/*
+* Builds a circuit without checking it, node 0 is connected to a ground component.
+*/
+struct Builder {
+    circuit: Circuit,
+}
+
+impl Builder {
+    fn new() -> Self {
+        Self {
+            circuit: Circuit::new("test".to_string(), 0),
+        }
+        .add(
+            "GND",
+            |i| GroundComponent(Ground::new(i)),
+            &[(GroundConnection, "0")],
+        )
+    }
+
+    fn add(
+        mut self,
+        name: &str,
+        component: impl FnOnce(Identifer) -> Component,
+        pins: &[(ConnectionType, &str)],
+    ) -> Self {
+        let id = self.circuit.components.len();
+        let identifer = Identifer::new(name.to_string(), id, name.to_string(), name.to_string());
+        self.circuit.add_component(component(identifer));
+        for (pin, node) in pins {
+            self.circuit.connect(name, *pin, node).unwrap();
+        }
+        self
+    }
+
+    fn resistor(self, name: &str, anode: &str, cathode: &str) -> Self {
+        self.add(
+            name,
+            |i| ResistorComponent(Resistor::new(i, 1e3)),
+            &[(Anode, anode), (Cathode, cathode)],
+        )
+    }
+
+    fn voltage_source(self, name: &str, anode: &str, cathode: &str) -> Self {
+        self.add(
+            name,
+            |i| DCVoltageSourceComponent(DCVoltageSource::new(i, 1.0)),
+            &[(Anode, anode), (Cathode, cathode)],
+        )
+    }
+
+    fn issues(&self) -> Vec<TopologyIssue> {
+        self.circuit.validate()
+    }
+}
+
+#[test]
+fn a_valid_circuit_has_no_issues() {
+    let builder = Builder::new()
+        .voltage_source("V1", "a", "0")
+        .resistor("R1", "a", "0");
+    assert_eq!(builder.issues(), []);
+}
+
+#[test]
+fn unconnected_pin() {
+    let builder = Builder::new()
+        .voltage_source("V1", "a", "0")
+        .resistor("R1", "a", "0")
+        .add(
+            "R2",
+            |i| ResistorComponent(Resistor::new(i, 1e3)),
+            &[(Anode, "a")],
+        );
+    assert!(builder.issues().contains(&TopologyIssue::UnconnectedPin {
+        component: "R2".to_string(),
+        pin: Cathode,
+    }));
+}
+
+#[test]
+fn empty_node() {
+    let mut builder = Builder::new()
+        .voltage_source("V1", "a", "0")
+        .resistor("R1", "a", "0");
+    builder.circuit.add_named_node("b");
+    assert_eq!(
+        builder.issues(),
+        [TopologyIssue::EmptyNode {
+            node: "b".to_string()
+        }]
+    );
+}
+
+#[test]
+fn unknown_control() {
+    let builder = Builder::new().resistor("R1", "a", "0").add(
+        "H1",
+        |i| CCVoltageSourceComponent(CCVoltageSource::new(i, 1.0, "V9".to_string())),
+        &[(Pin0, "a"), (Pin1, "0")],
+    );
+    assert_eq!(
+        builder.issues(),
+        [TopologyIssue::UnknownControl {
+            component: "H1".to_string(),
+            control: "V9".to_string(),
+        }]
+    );
+}
+
+#[test]
+fn single_connection_node() {
+    let builder = Builder::new()
+        .voltage_source("V1", "a", "0")
+        .resistor("R1", "a", "b");
+    assert_eq!(
+        builder.issues(),
+        [TopologyIssue::SingleConnectionNode {
+            node: "b".to_string(),
+            component: "R1".to_string(),
+        }]
+    );
+}
+
+#[test]
+fn a_pin_connected_twice_is_still_a_single_connection() {
+    let mut builder = Builder::new()
+        .voltage_source("V1", "a", "0")
+        .resistor("R1", "a", "b");
+    let r1 = builder.circuit.find_component("R1").unwrap();
+    let b = builder.circuit.find_node("b").unwrap();
+    builder.circuit.connect_node(r1, b, Cathode).unwrap();
+    assert_eq!(builder.circuit.nodes[b].connections.len(), 1);
+    // a node read from an older document can still list it twice
+    builder.circuit.nodes[b]
+        .connections
+        .push(Connected(r1, Cathode));
+    assert_eq!(
+        builder.issues(),
+        [TopologyIssue::SingleConnectionNode {
+            node: "b".to_string(),
+            component: "R1".to_string(),
+        }]
+    );
+}
+
+#[test]
+fn no_ground_reference() {
+    let builder = Builder::new()
+        .voltage_source("V1", "a", "0")
+        .resistor("R1", "a", "0")
+        .resistor("R2", "x", "y")
+        .resistor("R3", "y", "x");
+    assert_eq!(
+        builder.issues(),
+        [TopologyIssue::NoGroundReference {
+            components: vec!["R2".to_string(), "R3".to_string()],
+        }]
+    );
+}
+
+#[test]
+fn a_node_only_sensed_by_control_pins_has_no_ground_reference() {
+    // nothing sets the potential of c, E1 and G1 only sense it
+    let builder = Builder::new()
+        .add(
+            "E1",
+            |i| VCVoltageSourceComponent(VCVoltageSource::new(i, 2.0)),
+            &[(Pin0, "a"), (Pin1, "0"), (Pin2, "c"), (Pin3, "0")],
+        )
+        .resistor("R1", "a", "0")
+        .add(
+            "G1",
+            |i| VCCurrentSourceComponent(VCCurrentSource::new(i, 1e-3)),
+            &[(Pin0, "b"), (Pin1, "0"), (Pin2, "c"), (Pin3, "0")],
+        )
+        .resistor("R2", "b", "0");
+    assert_eq!(
+        builder.issues(),
+        [TopologyIssue::NoGroundReference {
+            components: vec!["E1".to_string(), "G1".to_string()],
+        }]
+    );
+}
+
+#[test]
+fn voltage_source_loop() {
+    let builder = Builder::new()
+        .voltage_source("V1", "a", "0")
+        .voltage_source("V2", "a", "0")
+        .resistor("R1", "a", "0");
+    let issues = builder.issues();
+    assert_eq!(issues.len(), 1);
+    let TopologyIssue::VoltageSourceLoop { components } = &issues[0] else {
+        panic!("expected a loop of voltage sources, got {issues:?}");
+    };
+    assert!(components.contains(&"V1".to_string()));
+    assert!(components.contains(&"V2".to_string()));
+}
+
+#[test]
+fn current_source_cut_set() {
+    // a and b are only connected to ground through I1 and C1, which is open in DC
+    let builder = Builder::new()
+        .add(
+            "I1",
+            |i| DCCurrentSourceComponent(DCCurrentSource::new(i, 1e-3)),
+            &[(Anode, "0"), (Cathode, "a")],
+        )
+        .resistor("R1", "a", "b")
+        .add(
+            "C1",
+            |i| CapacitorComponent(Capacitor::new(i, 1e-6)),
+            &[(Anode, "b"), (Cathode, "0")],
+        );
+    assert_eq!(
+        builder.issues(),
+        [TopologyIssue::CurrentSourceCutSet {
+            components: vec!["I1".to_string(), "C1".to_string()],
+        }]
+    );
+}